@@ -0,0 +1,34 @@
+use crate::error::PokerError;
+use crate::events::AdminTransferred;
+use crate::state::PokerTable;
+use anchor_lang::prelude::*;
+
+/// Proposed admin accepts control of the table
+pub fn handler(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let table = &mut ctx.accounts.table;
+    let old_admin = table.creator;
+
+    table.creator = ctx.accounts.new_admin.key();
+    table.pending_admin = None;
+
+    msg!("Table {} admin transferred {} -> {}", table.table_id, old_admin, table.creator);
+
+    emit!(AdminTransferred {
+        table: table.key(),
+        old_admin,
+        new_admin: table.creator,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        constraint = table.pending_admin == Some(new_admin.key()) @ PokerError::NotAdmin
+    )]
+    pub table: Account<'info, PokerTable>,
+
+    pub new_admin: Signer<'info>,
+}
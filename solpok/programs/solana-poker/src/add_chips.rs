@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::state::{PokerTable, PlayerSeat};
+use crate::error::PokerError;
+use crate::events::{StackChangeReason, StackChanged};
+use crate::vault;
+
+/// Seated player tops up their stack between games
+///
+/// The new stack may not exceed the table's buy_in_max.
+pub fn handler(ctx: Context<AddChips>, amount: u64) -> Result<()> {
+    let table = &ctx.accounts.table;
+    let player_seat = &mut ctx.accounts.player_seat;
+
+    // Top-ups only between games
+    require!(table.current_game.is_none(), PokerError::GameInProgress);
+    require!(amount > 0, PokerError::InvalidBuyIn);
+
+    let new_stack = topped_up_stack(player_seat.chips, amount, table.buy_in_max)?;
+
+    vault::deposit(
+        table.token_mint,
+        &ctx.accounts.vault.to_account_info(),
+        &ctx.accounts.player.to_account_info(),
+        &ctx.accounts.player_token_account,
+        &ctx.accounts.vault_token_account,
+        &ctx.accounts.token_program,
+        &ctx.accounts.system_program,
+        amount,
+    )?;
+
+    let chips_before = player_seat.chips;
+    player_seat.chips = new_stack;
+
+    msg!(
+        "Player {} at seat {} added {} lamports, stack now {}",
+        ctx.accounts.player.key(),
+        player_seat.seat_index,
+        amount,
+        new_stack
+    );
+
+    emit!(StackChanged {
+        game: None,
+        seat: player_seat.seat_index,
+        player: player_seat.player,
+        chips_before,
+        chips_after: new_stack,
+        reason: StackChangeReason::TopUp,
+    });
+
+    Ok(())
+}
+
+/// Stack after adding `amount`, refused past buy_in_max
+fn topped_up_stack(chips: u64, amount: u64, buy_in_max: u64) -> Result<u64> {
+    let new_stack = chips
+        .checked_add(amount)
+        .ok_or(PokerError::ArithmeticOverflow)?;
+    require!(new_stack <= buy_in_max, PokerError::InvalidBuyIn);
+    Ok(new_stack)
+}
+
+#[derive(Accounts)]
+pub struct AddChips<'info> {
+    #[account(
+        constraint = !table.paused @ PokerError::TablePaused
+    )]
+    pub table: Account<'info, PokerTable>,
+
+    /// CHECK: Vault PDA to receive SOL
+    #[account(
+        mut,
+        seeds = [b"vault", table.key().as_ref()],
+        bump = table.vault_bump
+    )]
+    pub vault: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"player_seat", table.key().as_ref(), player.key().as_ref()],
+        bump = player_seat.bump
+    )]
+    pub player_seat: Account<'info, PlayerSeat>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// Player's token account for the table mint (token tables only)
+    #[account(mut)]
+    pub player_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Vault-owned token account for the table mint (token tables only)
+    #[account(mut)]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn top_up_up_to_buy_in_max() {
+        assert_eq!(topped_up_stack(600, 400, 1_000).unwrap(), 1_000);
+        assert_eq!(
+            topped_up_stack(600, 401, 1_000).unwrap_err(),
+            PokerError::InvalidBuyIn.into()
+        );
+    }
+
+    #[test]
+    fn stack_near_u64_max_overflows_gracefully() {
+        assert_eq!(
+            topped_up_stack(u64::MAX - 1, 2, u64::MAX).unwrap_err(),
+            PokerError::ArithmeticOverflow.into()
+        );
+        assert_eq!(
+            topped_up_stack(u64::MAX - 1, 1, u64::MAX).unwrap(),
+            u64::MAX
+        );
+    }
+}
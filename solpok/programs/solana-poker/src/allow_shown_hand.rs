@@ -0,0 +1,88 @@
+use crate::error::PokerError;
+use crate::state::{GameStage, PlayerSeat, PokerGame, PokerTable, RevealChoice};
+use anchor_lang::prelude::*;
+use inco_lightning::cpi::accounts::Allow;
+use inco_lightning::cpi::allow;
+use inco_lightning::program::IncoLightning;
+
+/// Backend lets a spectator decrypt a hand that was shown at showdown
+///
+/// Fails with HandNotShown unless the seat declared Show for this game via
+/// declare_showdown_action, so mucked hands are never opened.
+///
+/// Remaining accounts: [allowance_1, allowance_2] for the seat's hole cards.
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, AllowShownHand<'info>>) -> Result<()> {
+    let game = &ctx.accounts.game;
+    let seat = &ctx.accounts.player_seat;
+
+    require!(
+        seat.game == game.key() && seat.reveal_choice == RevealChoice::Show,
+        PokerError::HandNotShown
+    );
+    require!(
+        ctx.remaining_accounts.len() == 2,
+        PokerError::MissingAllowanceAccounts
+    );
+
+    let (handle_1, handle_2) = game
+        .get_player_hole_cards(seat.seat_index)
+        .ok_or(PokerError::InvalidSeatIndex)?;
+
+    let cpi_program = ctx.accounts.inco_lightning_program.to_account_info();
+    let authority = ctx.accounts.backend.to_account_info();
+    let spectator = ctx.accounts.spectator.to_account_info();
+
+    for (allowance_acc, handle) in ctx.remaining_accounts.iter().zip([handle_1, handle_2]) {
+        let cpi_ctx = CpiContext::new(
+            cpi_program.clone(),
+            Allow {
+                allowance_account: allowance_acc.clone(),
+                signer: authority.clone(),
+                allowed_address: spectator.clone(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+        );
+        allow(cpi_ctx, handle.0, true, spectator.key())?;
+    }
+
+    msg!(
+        "Spectator {} allowed shown hand of seat {}",
+        spectator.key(),
+        seat.seat_index
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AllowShownHand<'info> {
+    #[account(
+        constraint = table.backend == backend.key() @ PokerError::NotBackend
+    )]
+    pub table: Account<'info, PokerTable>,
+
+    #[account(
+        constraint = game.table == table.key() @ PokerError::NoActiveGame,
+        constraint = game.stage == GameStage::Playing @ PokerError::InvalidGameStage
+    )]
+    pub game: Account<'info, PokerGame>,
+
+    #[account(
+        seeds = [b"player_seat", table.key().as_ref(), player_seat.player.as_ref()],
+        bump = player_seat.bump
+    )]
+    pub player_seat: Account<'info, PlayerSeat>,
+
+    /// CHECK: spectator receiving decrypt access
+    pub spectator: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = backend.key() == game.backend_account @ PokerError::NotBackend
+    )]
+    pub backend: Signer<'info>,
+
+    pub inco_lightning_program: Program<'info, IncoLightning>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,79 @@
+use crate::error::PokerError;
+use crate::state::{GameStage, PokerGame, PokerTable, Street};
+use anchor_lang::prelude::*;
+use inco_lightning::cpi::accounts::Allow;
+use inco_lightning::cpi::allow;
+use inco_lightning::program::IncoLightning;
+
+/// Backend lets a non-player watch an already revealed street
+///
+/// Grants `spectator` decrypt access to the community cards of `street`,
+/// but only once reveal_street has opened that street to the table. Hole
+/// cards are never reachable through this instruction.
+///
+/// Remaining accounts: one allowance account per card on the street.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, AllowSpectator<'info>>,
+    street: Street,
+) -> Result<()> {
+    let game = &ctx.accounts.game;
+
+    require!(
+        game.community_revealed & street.card_mask() == street.card_mask(),
+        PokerError::CardNotRevealed
+    );
+
+    let cards = street.card_range();
+    require!(
+        ctx.remaining_accounts.len() == cards.len(),
+        PokerError::MissingAllowanceAccounts
+    );
+
+    let cpi_program = ctx.accounts.inco_lightning_program.to_account_info();
+    let authority = ctx.accounts.backend.to_account_info();
+    let spectator = ctx.accounts.spectator.to_account_info();
+
+    for (allowance_acc, card_idx) in ctx.remaining_accounts.iter().zip(cards) {
+        let cpi_ctx = CpiContext::new(
+            cpi_program.clone(),
+            Allow {
+                allowance_account: allowance_acc.clone(),
+                signer: authority.clone(),
+                allowed_address: spectator.clone(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+        );
+        allow(cpi_ctx, game.community_cards[card_idx].0, true, spectator.key())?;
+    }
+
+    msg!("Spectator {} allowed {:?} cards", spectator.key(), street);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AllowSpectator<'info> {
+    #[account(
+        constraint = table.backend == backend.key() @ PokerError::NotBackend
+    )]
+    pub table: Account<'info, PokerTable>,
+
+    #[account(
+        constraint = game.table == table.key() @ PokerError::NoActiveGame,
+        constraint = game.stage == GameStage::Playing @ PokerError::InvalidGameStage
+    )]
+    pub game: Account<'info, PokerGame>,
+
+    /// CHECK: spectator receiving decrypt access
+    pub spectator: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = backend.key() == game.backend_account @ PokerError::NotBackend
+    )]
+    pub backend: Signer<'info>,
+
+    pub inco_lightning_program: Program<'info, IncoLightning>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,99 @@
+use crate::error::PokerError;
+use crate::events::RevealApproved;
+use crate::state::{GameStage, PlayerSeat, PokerGame, PokerTable};
+use anchor_lang::prelude::*;
+use inco_lightning::cpi::accounts::Allow;
+use inco_lightning::cpi::allow;
+use inco_lightning::program::IncoLightning;
+
+/// Seat owner approves a pending reveal request
+///
+/// Signed by the player (consent) and co-signed by the backend, which holds
+/// Inco access to the hole cards. The requester is allowed to decrypt both
+/// hole cards and the request is cleared.
+///
+/// Remaining accounts: [allowance_1, allowance_2] for the seat's hole cards,
+/// keyed by the requester.
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, ApproveReveal<'info>>) -> Result<()> {
+    let game = &ctx.accounts.game;
+    let seat = &mut ctx.accounts.player_seat;
+    let requester = ctx.accounts.requester.to_account_info();
+
+    require!(
+        seat.reveal_requested_by == Some(requester.key()) && seat.reveal_request_game == game.key(),
+        PokerError::NoRevealRequest
+    );
+    require!(
+        ctx.remaining_accounts.len() == 2,
+        PokerError::MissingAllowanceAccounts
+    );
+
+    let (handle_1, handle_2) = game
+        .get_player_hole_cards(seat.seat_index)
+        .ok_or(PokerError::InvalidSeatIndex)?;
+
+    let cpi_program = ctx.accounts.inco_lightning_program.to_account_info();
+    let authority = ctx.accounts.backend.to_account_info();
+
+    for (allowance_acc, handle) in ctx.remaining_accounts.iter().zip([handle_1, handle_2]) {
+        let cpi_ctx = CpiContext::new(
+            cpi_program.clone(),
+            Allow {
+                allowance_account: allowance_acc.clone(),
+                signer: authority.clone(),
+                allowed_address: requester.clone(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+        );
+        allow(cpi_ctx, handle.0, true, requester.key())?;
+    }
+
+    seat.reveal_requested_by = None;
+    seat.reveal_request_game = Pubkey::default();
+
+    msg!("Seat {} revealed its hand to {}", seat.seat_index, requester.key());
+
+    emit!(RevealApproved {
+        game: game.key(),
+        seat_index: seat.seat_index,
+        requester: requester.key(),
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ApproveReveal<'info> {
+    #[account(
+        constraint = table.backend == backend.key() @ PokerError::NotBackend
+    )]
+    pub table: Account<'info, PokerTable>,
+
+    #[account(
+        constraint = game.table == table.key() @ PokerError::NoActiveGame,
+        constraint = game.stage == GameStage::Playing @ PokerError::InvalidGameStage
+    )]
+    pub game: Account<'info, PokerGame>,
+
+    #[account(
+        mut,
+        seeds = [b"player_seat", table.key().as_ref(), player.key().as_ref()],
+        bump = player_seat.bump
+    )]
+    pub player_seat: Account<'info, PlayerSeat>,
+
+    pub player: Signer<'info>,
+
+    /// CHECK: requester receiving decrypt access, checked against the pending request
+    pub requester: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = backend.key() == game.backend_account @ PokerError::NotBackend
+    )]
+    pub backend: Signer<'info>,
+
+    pub inco_lightning_program: Program<'info, IncoLightning>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,102 @@
+use crate::constants::TOTAL_CARDS_NEEDED;
+use crate::error::PokerError;
+use crate::state::{GameStage, PokerGame, PokerTable};
+use anchor_lang::prelude::*;
+use inco_lightning::cpi::accounts::VerifySignature;
+use inco_lightning::cpi::is_validsignature;
+use inco_lightning::program::IncoLightning;
+
+/// Record decrypted card values after checking Inco's attestation
+///
+/// The transaction carries the ed25519 instructions returned by Inco's
+/// attested decrypt for the handles at `slots` (0-9 = deal_cards, 10-14 =
+/// community_cards). The plaintexts are verified against the game's stored
+/// handles on-chain, then kept as card indices (plaintext mod 52) in
+/// `card_values` for rank_hands. Anyone holding decrypt access to a card
+/// (backend, or a player for their own hole cards) can attest it, and
+/// cards can be attested over several transactions.
+pub fn handler(ctx: Context<AttestCards>, slots: Vec<u8>, plaintexts: Vec<Vec<u8>>) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+
+    require!(game.cards_processed, PokerError::CardsNotProcessed);
+    require!(
+        !slots.is_empty()
+            && slots.len() == plaintexts.len()
+            && slots.len() <= TOTAL_CARDS_NEEDED as usize,
+        PokerError::InvalidCardCount
+    );
+
+    let mut handles = Vec::with_capacity(slots.len());
+    let mut seen_slots: u16 = 0;
+    for &slot in slots.iter() {
+        let handle = game
+            .card_handle(slot as usize)
+            .ok_or(PokerError::InvalidCard)?;
+        require!(handle.0 != 0, PokerError::CardsNotProcessed);
+        require!(seen_slots & (1 << slot) == 0, PokerError::DuplicateCard);
+        seen_slots |= 1 << slot;
+        handles.push(handle.0.to_le_bytes().to_vec());
+    }
+
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.inco_lightning_program.to_account_info(),
+        VerifySignature {
+            instructions: ctx.accounts.instructions.to_account_info(),
+            signer: ctx.accounts.attester.to_account_info(),
+        },
+    );
+    is_validsignature(
+        cpi_ctx,
+        slots.len() as u8,
+        Some(handles),
+        Some(plaintexts.clone()),
+    )?;
+
+    for (&slot, plaintext) in slots.iter().zip(plaintexts.iter()) {
+        game.card_values[slot as usize] = card_index(plaintext)?;
+        game.cards_attested_mask |= 1 << slot;
+    }
+
+    msg!(
+        "Attested {} cards, cards_attested_mask={:#017b}",
+        slots.len(),
+        game.cards_attested_mask
+    );
+
+    Ok(())
+}
+
+/// Little-endian plaintext bytes to a card index (0-51)
+fn card_index(plaintext: &[u8]) -> Result<u8> {
+    require!(
+        plaintext.iter().skip(16).all(|&b| b == 0),
+        PokerError::InvalidCard
+    );
+    let mut bytes = [0u8; 16];
+    for (dst, src) in bytes.iter_mut().zip(plaintext.iter()) {
+        *dst = *src;
+    }
+    Ok((u128::from_le_bytes(bytes) % 52) as u8)
+}
+
+#[derive(Accounts)]
+pub struct AttestCards<'info> {
+    #[account(constraint = !table.paused @ PokerError::TablePaused)]
+    pub table: Account<'info, PokerTable>,
+
+    #[account(
+        mut,
+        constraint = game.table == table.key() @ PokerError::NoActiveGame,
+        constraint = game.stage == GameStage::Playing @ PokerError::InvalidGameStage
+    )]
+    pub game: Account<'info, PokerGame>,
+
+    /// Address that requested the attested decrypt
+    pub attester: Signer<'info>,
+
+    /// CHECK: instructions sysvar holding the ed25519 attestations
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: AccountInfo<'info>,
+
+    pub inco_lightning_program: Program<'info, IncoLightning>,
+}
@@ -0,0 +1,106 @@
+use crate::constants::MAX_PLAYERS;
+use crate::error::PokerError;
+use crate::events::CardsDealt;
+use crate::state::{PlayerSeat, PokerGame, PokerTable};
+use anchor_lang::prelude::*;
+use inco_lightning::cpi::accounts::Allow;
+use inco_lightning::cpi::allow;
+use inco_lightning::program::IncoLightning;
+
+/// Number of remaining accounts per seat: [player_seat, player, allowance_1, allowance_2]
+const ACCOUNTS_PER_SEAT: usize = 4;
+
+/// Backend grants several players decrypt access to their hole cards at once
+///
+/// Same as reveal_hand, but for a slice of seats in a single transaction.
+/// The caller picks how many seats go into each call so the allow CPIs stay
+/// under the compute limit; seats can be split across as many calls as needed.
+///
+/// Remaining accounts: groups of [player_seat, player, allowance_1, allowance_2]
+/// for each seat in the slice.
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, BatchAllowHoleCards<'info>>) -> Result<()> {
+    let game = &ctx.accounts.game;
+
+    require!(game.cards_processed, PokerError::CardsNotProcessed);
+
+    let remaining = &ctx.remaining_accounts;
+    require!(
+        !remaining.is_empty() && remaining.len().is_multiple_of(ACCOUNTS_PER_SEAT),
+        PokerError::MissingAllowanceAccounts
+    );
+    require!(
+        remaining.len() / ACCOUNTS_PER_SEAT <= MAX_PLAYERS as usize,
+        PokerError::InvalidSeatIndex
+    );
+
+    let table_key = ctx.accounts.table.key();
+    let cpi_program = ctx.accounts.inco_lightning_program.to_account_info();
+    let authority = ctx.accounts.backend.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+
+    for group in remaining.chunks(ACCOUNTS_PER_SEAT) {
+        let seat_info = &group[0];
+        let player_info = &group[1];
+
+        let seat: Account<PlayerSeat> = Account::try_from(seat_info)?;
+
+        let (expected, _) = Pubkey::find_program_address(
+            &[b"player_seat", table_key.as_ref(), seat.player.as_ref()],
+            ctx.program_id,
+        );
+        require!(expected == *seat_info.key, PokerError::PlayerNotAtTable);
+        require!(player_info.key == &seat.player, PokerError::PlayerNotAtTable);
+        require!(!seat.is_folded, PokerError::PlayerFolded);
+
+        let (handle_1, handle_2) = game
+            .get_player_hole_cards(seat.seat_index)
+            .ok_or(PokerError::InvalidSeatIndex)?;
+        // Never hand out an unset slot
+        require!(
+            handle_1.0 != 0 && handle_2.0 != 0,
+            PokerError::InvalidCardCount
+        );
+
+        for (allowance_acc, handle) in [(&group[2], handle_1), (&group[3], handle_2)] {
+            let cpi_ctx = CpiContext::new(
+                cpi_program.clone(),
+                Allow {
+                    allowance_account: allowance_acc.clone(),
+                    signer: authority.clone(),
+                    allowed_address: player_info.clone(),
+                    system_program: system_program.clone(),
+                },
+            );
+            allow(cpi_ctx, handle.0, true, seat.player)?;
+        }
+
+        msg!("Allowed hole cards for seat {}", seat.seat_index);
+
+        emit!(CardsDealt {
+            game: game.key(),
+            seat: seat.seat_index,
+        });
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct BatchAllowHoleCards<'info> {
+    pub table: Account<'info, PokerTable>,
+
+    #[account(
+        constraint = game.table == table.key() @ PokerError::NoActiveGame
+    )]
+    pub game: Account<'info, PokerGame>,
+
+    #[account(
+        mut,
+        constraint = backend.key() == game.backend_account @ PokerError::NotBackend
+    )]
+    pub backend: Signer<'info>,
+
+    pub inco_lightning_program: Program<'info, IncoLightning>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::error::PokerError;
+use crate::events::{StackChangeReason, StackChanged, StageAdvanced};
+use crate::state::{GameStage, PlayerSeat, PokerGame, PokerTable};
+use crate::vault;
+
+/// Cancel a game that never finished dealing
+///
+/// Only allowed while the game is still in the Waiting stage (cards not yet
+/// processed). The admin can cancel at any time; once the game's
+/// waiting_deadline has passed anyone can, so a stalled deal never locks
+/// the table or the posted blinds. Every seat already initialized for the
+/// game (the blind posters) gets its blind back, its whole stack refunded
+/// from the vault and its PlayerSeat closed. Seats that sat out the hand
+/// stay seated. The game account is closed and the table is free to start
+/// a new game.
+///
+/// Remaining accounts: pairs of [player_seat, player_wallet] for each seat
+/// that posted a blind. On token tables pass triples of
+/// [player_seat, player_wallet, player_token_account]; chips are refunded
+/// to the token account and seat rent to the wallet.
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, CancelGame<'info>>) -> Result<()> {
+    let table = &mut ctx.accounts.table;
+    let game = &mut ctx.accounts.game;
+
+    require!(table.current_game == Some(game.key()), PokerError::NoActiveGame);
+    require!(
+        ctx.accounts.authority.key() == table.creator
+            || Clock::get()?.unix_timestamp > game.waiting_deadline,
+        PokerError::NotAdmin
+    );
+
+    let remaining = &ctx.remaining_accounts;
+    let stride = if table.token_mint.is_some() { 3 } else { 2 };
+    require!(
+        remaining.len().is_multiple_of(stride),
+        PokerError::InvalidRefundAccounts
+    );
+
+    let table_key = table.key();
+    let game_key = game.key();
+    let mut restored: u64 = 0;
+    let mut refunded: u8 = 0;
+
+    for group in remaining.chunks(stride) {
+        let seat_info = &group[0];
+        let player_wallet = &group[1];
+        let refund_account = &group[stride - 1];
+
+        let mut seat: Account<PlayerSeat> = Account::try_from(seat_info)?;
+
+        let (expected, _) = Pubkey::find_program_address(
+            &[b"player_seat", table_key.as_ref(), seat.player.as_ref()],
+            ctx.program_id,
+        );
+        require!(expected == *seat_info.key, PokerError::PlayerNotAtTable);
+        require!(seat.game == game_key, PokerError::PlayerNotAtTable);
+        require!(player_wallet.key == &seat.player, PokerError::PlayerNotAtTable);
+
+        let chips_before = seat.chips;
+        let stack = seat
+            .chips
+            .checked_add(seat.total_bet)
+            .ok_or(PokerError::ArithmeticOverflow)?;
+        restored = restored
+            .checked_add(seat.total_bet)
+            .ok_or(PokerError::ArithmeticOverflow)?;
+
+        emit!(StackChanged {
+            game: Some(game_key),
+            seat: seat.seat_index,
+            player: seat.player,
+            chips_before,
+            chips_after: stack,
+            reason: StackChangeReason::BlindReturned,
+        });
+
+        vault::pay_out(
+            table_key,
+            table.token_mint,
+            &ctx.accounts.vault,
+            table.vault_bump,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.system_program,
+            refund_account,
+            &seat.player,
+            stack,
+        )?;
+
+        emit!(StackChanged {
+            game: Some(game_key),
+            seat: seat.seat_index,
+            player: seat.player,
+            chips_before: stack,
+            chips_after: 0,
+            reason: StackChangeReason::Refund,
+        });
+
+        seat.chips = 0;
+        seat.current_bet = 0;
+        seat.total_bet = 0;
+        table.seats_taken &= !(1u8 << seat.seat_index);
+        seat.close(player_wallet.clone())?;
+        refunded = refunded.saturating_add(1);
+    }
+
+    // Every blind collected into the pot must be handed back
+    require!(restored == game.pot, PokerError::InvalidRefundAccounts);
+
+    table.player_count = table.player_count.saturating_sub(refunded);
+    table.current_game = None;
+    game.stage = GameStage::Finished;
+    game.pot = 0;
+
+    msg!(
+        "Game {} cancelled, {} lamports of blinds returned, {} seats refunded",
+        game.game_id,
+        restored,
+        refunded
+    );
+
+    emit!(StageAdvanced {
+        game: game_key,
+        from: GameStage::Waiting,
+        to: GameStage::Finished,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelGame<'info> {
+    #[account(mut)]
+    pub table: Account<'info, PokerTable>,
+
+    #[account(
+        mut,
+        close = backend,
+        constraint = game.table == table.key() @ PokerError::NoActiveGame,
+        constraint = game.stage == GameStage::Waiting @ PokerError::InvalidGameStage
+    )]
+    pub game: Account<'info, PokerGame>,
+
+    /// CHECK: Vault PDA to pay refunds from
+    #[account(
+        mut,
+        seeds = [b"vault", table.key().as_ref()],
+        bump = table.vault_bump
+    )]
+    pub vault: AccountInfo<'info>,
+
+    /// Vault-owned token account for the table mint (token tables only)
+    #[account(mut)]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Backend that paid for the game account, receives its rent
+    #[account(
+        mut,
+        constraint = backend.key() == game.backend_account @ PokerError::NotBackend
+    )]
+    pub backend: AccountInfo<'info>,
+
+    /// Table admin, or anyone once the waiting deadline has passed
+    pub authority: Signer<'info>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,172 @@
+use crate::error::PokerError;
+use crate::state::PokerTable;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+/// Close an empty table and reclaim its rent
+///
+/// Only the table creator can close, and only once every player has been
+/// refunded and no game is live. Whatever is left in the vault PDA (dust
+/// and rent) is drained back to the creator, and so is any rake still in
+/// the rake vault; on token tables the vault and rake token accounts are
+/// closed as well, with leftover rake tokens sent to the creator's token
+/// account first.
+pub fn handler(ctx: Context<CloseTable>) -> Result<()> {
+    let table = &ctx.accounts.table;
+
+    require!(table.current_game.is_none(), PokerError::GameInProgress);
+    require!(table.player_count == 0, PokerError::TableNotEmpty);
+
+    let table_key = table.key();
+    let seeds = &[b"vault", table_key.as_ref(), &[table.vault_bump]];
+    let signer = &[&seeds[..]];
+    let rake_seeds = &[b"rake_vault", table_key.as_ref(), &[ctx.bumps.rake_vault]];
+    let rake_signer = &[&rake_seeds[..]];
+
+    if let Some(mint) = table.token_mint {
+        let (Some(vault_token_account), Some(token_program)) = (
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.token_program,
+        ) else {
+            return err!(PokerError::MixedFundingMode);
+        };
+        require!(
+            vault_token_account.mint == mint
+                && vault_token_account.owner == ctx.accounts.vault.key(),
+            PokerError::InvalidTokenAccount
+        );
+
+        token::close_account(CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token::CloseAccount {
+                account: vault_token_account.to_account_info(),
+                destination: ctx.accounts.creator.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer,
+        ))?;
+
+        if let Some(rake_token_account) = &ctx.accounts.rake_token_account {
+            require!(
+                rake_token_account.mint == mint
+                    && rake_token_account.owner == ctx.accounts.rake_vault.key(),
+                PokerError::InvalidTokenAccount
+            );
+            if rake_token_account.amount > 0 {
+                let Some(creator_token_account) = &ctx.accounts.creator_token_account else {
+                    return err!(PokerError::MixedFundingMode);
+                };
+                require!(
+                    creator_token_account.mint == mint
+                        && creator_token_account.owner == ctx.accounts.creator.key(),
+                    PokerError::InvalidTokenAccount
+                );
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        token_program.to_account_info(),
+                        token::Transfer {
+                            from: rake_token_account.to_account_info(),
+                            to: creator_token_account.to_account_info(),
+                            authority: ctx.accounts.rake_vault.to_account_info(),
+                        },
+                        rake_signer,
+                    ),
+                    rake_token_account.amount,
+                )?;
+            }
+
+            token::close_account(CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                token::CloseAccount {
+                    account: rake_token_account.to_account_info(),
+                    destination: ctx.accounts.creator.to_account_info(),
+                    authority: ctx.accounts.rake_vault.to_account_info(),
+                },
+                rake_signer,
+            ))?;
+        }
+    }
+
+    let vault_balance = ctx.accounts.vault.lamports();
+    if vault_balance > 0 {
+        let cpi_accounts = anchor_lang::system_program::Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.creator.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.system_program.to_account_info();
+        anchor_lang::system_program::transfer(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+            vault_balance,
+        )?;
+    }
+
+    // Rake not yet withdrawn goes to the creator along with the rent
+    let rake_balance = ctx.accounts.rake_vault.lamports();
+    if rake_balance > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.rake_vault.to_account_info(),
+                    to: ctx.accounts.creator.to_account_info(),
+                },
+                rake_signer,
+            ),
+            rake_balance,
+        )?;
+    }
+
+    msg!(
+        "Table {} closed, {} lamports drained from vault, {} from rake vault",
+        table.table_id,
+        vault_balance,
+        rake_balance
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseTable<'info> {
+    #[account(
+        mut,
+        close = creator,
+        has_one = creator @ PokerError::NotAdmin
+    )]
+    pub table: Account<'info, PokerTable>,
+
+    /// CHECK: Vault PDA drained back to the creator
+    #[account(
+        mut,
+        seeds = [b"vault", table.key().as_ref()],
+        bump = table.vault_bump
+    )]
+    pub vault: AccountInfo<'info>,
+
+    /// Vault-owned token account for the table mint (token tables only)
+    #[account(mut)]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Rake vault PDA drained back to the creator
+    #[account(
+        mut,
+        seeds = [b"rake_vault", table.key().as_ref()],
+        bump
+    )]
+    pub rake_vault: AccountInfo<'info>,
+
+    /// Rake-vault-owned token account for the table mint, if one was created
+    #[account(mut)]
+    pub rake_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Creator's token account, receives leftover rake tokens
+    #[account(mut)]
+    pub creator_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub system_program: Program<'info, System>,
+}
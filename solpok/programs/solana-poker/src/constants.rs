@@ -15,5 +15,17 @@ pub const COMMUNITY_CARDS: u8 = 5;
 
 pub const TOTAL_CARDS_NEEDED: u8 = 15;
 
+/// input_type values Inco's new_euint128 accepts (0 = ciphertext from the SDK's encryptValue)
+pub const ACCEPTED_INPUT_TYPES: &[u8] = &[0];
+
+/// Seconds a game may sit in Waiting before anyone can cancel it
+pub const WAITING_TIMEOUT_SECS: i64 = 600;
+
+/// Basis-point denominator for rake percentages (100%)
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+/// Minimum buy-in, in big blinds, a table may be configured with
+pub const MIN_BUY_IN_BIG_BLINDS: u64 = 2;
+
 pub const SMALL_BLIND_MULTIPLIER: u64 = 1;
 pub const BIG_BLIND_MULTIPLIER: u64 = 2;
@@ -1,9 +1,16 @@
 use anchor_lang::prelude::*;
 use crate::state::PokerTable;
 use crate::error::PokerError;
-use crate::constants::MAX_PLAYERS;
+use crate::constants::{
+    BIG_BLIND_MULTIPLIER, BPS_DENOMINATOR, MAX_PLAYERS, MIN_BUY_IN_BIG_BLINDS, MIN_PLAYERS,
+};
 
 /// Creates a new poker table with configuration
+///
+/// Passing big_blind = 0 uses the standard BIG_BLIND_MULTIPLIER x small_blind.
+/// With a rake_referrer set, referrer_share_bps of every rake is paid to the
+/// referrer and the rest (BPS_DENOMINATOR - referrer_share_bps) to the rake vault.
+#[allow(clippy::too_many_arguments)]
 pub fn handler(
     ctx: Context<CreateTable>,
     table_id: u64,
@@ -11,11 +18,44 @@ pub fn handler(
     buy_in_min: u64,
     buy_in_max: u64,
     small_blind: u64,
+    big_blind: u64,
     backend_account: Pubkey,
+    token_mint: Option<Pubkey>,
+    rake_bps: u16,
+    rake_cap: u64,
+    rake_referrer: Option<Pubkey>,
+    referrer_share_bps: u16,
 ) -> Result<()> {
-    require!(max_players >= 2 && max_players <= MAX_PLAYERS, PokerError::InvalidBuyIn);
-    require!(buy_in_min > 0 && buy_in_min <= buy_in_max, PokerError::InvalidBuyIn);
-    require!(small_blind > 0, PokerError::InvalidBuyIn);
+    require!(max_players >= MIN_PLAYERS, PokerError::NotEnoughPlayers);
+    // Seat bitmasks and per-seat arrays are sized for MAX_PLAYERS
+    require!(max_players <= MAX_PLAYERS, PokerError::TooManySeats);
+    require!(buy_in_min <= buy_in_max, PokerError::InvalidTableConfig);
+    let big_blind = resolve_blinds(small_blind, big_blind, buy_in_min)?;
+    require!(rake_bps <= BPS_DENOMINATOR, PokerError::InvalidRake);
+    // The two rake shares must add up to the whole rake
+    require!(
+        referrer_share_bps <= BPS_DENOMINATOR
+            && (rake_referrer.is_some() || referrer_share_bps == 0),
+        PokerError::InvalidRake
+    );
+
+    // Make the rake vault rent-exempt up front so small rakes can always land
+    if rake_bps > 0 && token_mint.is_none() {
+        let rent_exempt = Rent::get()?.minimum_balance(0);
+        let missing = rent_exempt.saturating_sub(ctx.accounts.rake_vault.lamports());
+        if missing > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.creator.to_account_info(),
+                        to: ctx.accounts.rake_vault.to_account_info(),
+                    },
+                ),
+                missing,
+            )?;
+        }
+    }
 
     let table = &mut ctx.accounts.table;
     table.creator = ctx.accounts.creator.key();
@@ -25,21 +65,66 @@ pub fn handler(
     table.buy_in_min = buy_in_min;
     table.buy_in_max = buy_in_max;
     table.small_blind = small_blind;
+    table.big_blind = big_blind;
+    table.token_mint = token_mint;
+    table.rake_bps = rake_bps;
+    table.rake_cap = rake_cap;
+    table.rake_referrer = rake_referrer;
+    table.referrer_share_bps = referrer_share_bps;
     table.current_game = None;
     table.player_count = 0;
+    table.seats_taken = 0;
     table.bump = ctx.bumps.table;
+    table.vault_bump = ctx.bumps.vault;
+    table.pending_admin = None;
+    table.paused = false;
+    table.games_played = 0;
 
     msg!("Poker table {} created by {} with backend {}", table_id, ctx.accounts.creator.key(), backend_account);
     Ok(())
 }
 
+/// Space for a new table, or TableIdTaken if the table PDA is already in use
+///
+/// The table PDA is keyed by (creator, table_id), so an existing account
+/// means this creator already used the id. A failed system-program CPI
+/// aborts the whole transaction, so `init`'s "already in use" error can't
+/// be caught afterwards; this runs as `init` sizes the account, before the
+/// create CPI.
+fn unclaimed_table_space(table: &AccountInfo) -> Result<usize> {
+    require!(table.data_is_empty(), PokerError::TableIdTaken);
+    Ok(PokerTable::LEN)
+}
+
+/// Validate a blind pair and return the big blind to store
+///
+/// big_blind = 0 means BIG_BLIND_MULTIPLIER x small_blind. Shared with
+/// set_blinds so stakes changes follow the same rules as new tables.
+pub(crate) fn resolve_blinds(small_blind: u64, big_blind: u64, buy_in_min: u64) -> Result<u64> {
+    require!(small_blind > 0, PokerError::InvalidTableConfig);
+    let big_blind = if big_blind == 0 {
+        small_blind
+            .checked_mul(BIG_BLIND_MULTIPLIER)
+            .ok_or(PokerError::ArithmeticOverflow)?
+    } else {
+        big_blind
+    };
+    require!(big_blind >= small_blind, PokerError::InvalidTableConfig);
+    // Every player must be able to cover the blinds
+    let min_stack = big_blind
+        .checked_mul(MIN_BUY_IN_BIG_BLINDS)
+        .ok_or(PokerError::ArithmeticOverflow)?;
+    require!(buy_in_min >= min_stack, PokerError::InvalidTableConfig);
+    Ok(big_blind)
+}
+
 #[derive(Accounts)]
 #[instruction(table_id: u64)]
 pub struct CreateTable<'info> {
     #[account(
         init,
         payer = creator,
-        space = PokerTable::LEN,
+        space = unclaimed_table_space(table)?,
         seeds = [b"table", creator.key().as_ref(), &table_id.to_le_bytes()],
         bump
     )]
@@ -53,9 +138,38 @@ pub struct CreateTable<'info> {
     /// CHECK: This is a PDA vault that will hold SOL
     pub vault: AccountInfo<'info>,
 
+    /// CHECK: Rake vault PDA that collects the table's rake
+    #[account(
+        mut,
+        seeds = [b"rake_vault", table.key().as_ref()],
+        bump
+    )]
+    pub rake_vault: AccountInfo<'info>,
+
     /// Player creating the table
     #[account(mut)]
     pub creator: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_big_blind_overflow_is_an_error() {
+        assert_eq!(
+            resolve_blinds(u64::MAX / 2 + 1, 0, u64::MAX).unwrap_err(),
+            PokerError::ArithmeticOverflow.into()
+        );
+    }
+
+    #[test]
+    fn minimum_stack_overflow_is_an_error() {
+        assert_eq!(
+            resolve_blinds(1, u64::MAX / 2 + 1, u64::MAX).unwrap_err(),
+            PokerError::ArithmeticOverflow.into()
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use crate::error::PokerError;
+use crate::state::{GameStage, PlayerSeat, PokerGame, PokerTable, RevealChoice};
+use anchor_lang::prelude::*;
+
+/// Player declares whether they show or muck their hand at showdown
+///
+/// Each seat still in the hand declares once per game. Only shown hands
+/// can be opened to spectators via allow_shown_hand; a mucked hand's hole
+/// cards stay private to the player. Declaring marks the seat as having
+/// reached showdown, so a pot two seats contest can only be settled from
+/// rank_hands (see PokerGame::requires_ranking). Showing order (last
+/// aggressor first) is not checked on-chain: betting runs off-chain, so the
+/// backend decides who is still in the hand and when each seat may declare.
+pub fn handler(ctx: Context<DeclareShowdownAction>, choice: RevealChoice) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+    let seat = &mut ctx.accounts.player_seat;
+
+    require!(game.cards_processed, PokerError::CardsNotProcessed);
+    require!(choice != RevealChoice::Undecided, PokerError::InvalidRevealChoice);
+    require!(
+        seat.game != game.key() || seat.reveal_choice == RevealChoice::Undecided,
+        PokerError::InvalidRevealChoice
+    );
+
+    seat.game = game.key();
+    seat.reveal_choice = choice;
+    game.showdown_mask |= 1 << seat.seat_index;
+
+    msg!("Seat {} declared {:?} at showdown", seat.seat_index, choice);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DeclareShowdownAction<'info> {
+    pub table: Account<'info, PokerTable>,
+
+    #[account(
+        mut,
+        constraint = game.table == table.key() @ PokerError::NoActiveGame,
+        constraint = game.stage == GameStage::Playing @ PokerError::InvalidGameStage
+    )]
+    pub game: Account<'info, PokerGame>,
+
+    #[account(
+        mut,
+        seeds = [b"player_seat", table.key().as_ref(), player.key().as_ref()],
+        bump = player_seat.bump
+    )]
+    pub player_seat: Account<'info, PlayerSeat>,
+
+    pub player: Signer<'info>,
+}
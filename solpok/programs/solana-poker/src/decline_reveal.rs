@@ -0,0 +1,51 @@
+use crate::error::PokerError;
+use crate::events::RevealDeclined;
+use crate::state::{GameStage, PlayerSeat, PokerGame, PokerTable};
+use anchor_lang::prelude::*;
+
+/// Seat owner turns down the pending reveal request
+///
+/// Clears the request without opening anything, so the seat can take a
+/// request from someone else. Only the seat's owner can decline.
+pub fn handler(ctx: Context<DeclineReveal>) -> Result<()> {
+    let game = &ctx.accounts.game;
+    let seat = &mut ctx.accounts.player_seat;
+
+    let requester = seat
+        .reveal_requested_by
+        .filter(|_| seat.reveal_request_game == game.key())
+        .ok_or(PokerError::NoRevealRequest)?;
+
+    seat.reveal_requested_by = None;
+    seat.reveal_request_game = Pubkey::default();
+
+    msg!("Seat {} declined to reveal to {}", seat.seat_index, requester);
+
+    emit!(RevealDeclined {
+        game: game.key(),
+        seat_index: seat.seat_index,
+        requester,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DeclineReveal<'info> {
+    pub table: Account<'info, PokerTable>,
+
+    #[account(
+        constraint = game.table == table.key() @ PokerError::NoActiveGame,
+        constraint = game.stage == GameStage::Playing @ PokerError::InvalidGameStage
+    )]
+    pub game: Account<'info, PokerGame>,
+
+    #[account(
+        mut,
+        seeds = [b"player_seat", table.key().as_ref(), player.key().as_ref()],
+        bump = player_seat.bump
+    )]
+    pub player_seat: Account<'info, PlayerSeat>,
+
+    pub player: Signer<'info>,
+}
@@ -0,0 +1,75 @@
+use crate::error::PokerError;
+use crate::state::{PokerGame, PokerTable};
+use anchor_lang::prelude::*;
+use inco_lightning::cpi::accounts::Allow;
+use inco_lightning::cpi::allow;
+use inco_lightning::program::IncoLightning;
+
+/// Number of card handles in a game: 10 hole cards + 5 community cards
+const POOL_SIZE: usize = 15;
+
+/// DEV ONLY: grant the table admin decrypt access to every card in the game
+///
+/// Only compiled with the `dev-reveal` feature, for debugging the FHE flow
+/// on localnet. Never build a deployable program with this feature on.
+///
+/// Remaining accounts: 15 allowance accounts, hole cards (deal_cards 0-9)
+/// then community cards (0-4).
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, DevRevealPool<'info>>) -> Result<()> {
+    let game = &ctx.accounts.game;
+
+    require!(game.cards_processed, PokerError::CardsNotProcessed);
+    require!(
+        ctx.remaining_accounts.len() == POOL_SIZE,
+        PokerError::MissingAllowanceAccounts
+    );
+
+    let cpi_program = ctx.accounts.inco_lightning_program.to_account_info();
+    let authority = ctx.accounts.backend.to_account_info();
+    let admin = ctx.accounts.admin.to_account_info();
+
+    let handles = game.deal_cards.iter().chain(game.community_cards.iter());
+    for (allowance_acc, handle) in ctx.remaining_accounts.iter().zip(handles) {
+        let cpi_ctx = CpiContext::new(
+            cpi_program.clone(),
+            Allow {
+                allowance_account: allowance_acc.clone(),
+                signer: authority.clone(),
+                allowed_address: admin.clone(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+        );
+        allow(cpi_ctx, handle.0, true, admin.key())?;
+    }
+
+    msg!("DEV: all {} cards allowed to admin {}", POOL_SIZE, admin.key());
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DevRevealPool<'info> {
+    #[account(
+        constraint = table.backend == backend.key() @ PokerError::NotBackend
+    )]
+    pub table: Account<'info, PokerTable>,
+
+    #[account(
+        constraint = game.table == table.key() @ PokerError::NoActiveGame
+    )]
+    pub game: Account<'info, PokerGame>,
+
+    /// CHECK: table admin receiving decrypt access
+    #[account(address = table.creator @ PokerError::NotAdmin)]
+    pub admin: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = backend.key() == game.backend_account @ PokerError::NotBackend
+    )]
+    pub backend: Signer<'info>,
+
+    pub inco_lightning_program: Program<'info, IncoLightning>,
+
+    pub system_program: Program<'info, System>,
+}
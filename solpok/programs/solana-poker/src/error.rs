@@ -97,4 +97,91 @@ pub enum PokerError {
 
     #[msg("Invalid refund accounts provided")]
     InvalidRefundAccounts,
+
+    #[msg("SlotHashes sysvar has no entries")]
+    InvalidSlotHashes,
+
+    #[msg("Token accounts do not match the table's funding mode")]
+    MixedFundingMode,
+
+    #[msg("Token account has the wrong mint or owner")]
+    InvalidTokenAccount,
+
+    #[msg("Invalid card value")]
+    InvalidCard,
+
+    #[msg("Card appears more than once")]
+    DuplicateCard,
+
+    #[msg("Hands already ranked for this game")]
+    HandsAlreadyRanked,
+
+    #[msg("Winner does not hold the best ranked hand")]
+    NotBestHand,
+
+    #[msg("Previous street has not been revealed")]
+    StreetOutOfOrder,
+
+    #[msg("Table still has seated players")]
+    TableNotEmpty,
+
+    #[msg("Invalid rake configuration")]
+    InvalidRake,
+
+    #[msg("Table has more seats than supported")]
+    TooManySeats,
+
+    #[msg("Game has already been settled")]
+    AlreadySettled,
+
+    #[msg("Chip amount overflow")]
+    ArithmeticOverflow,
+
+    #[msg("Card has not been revealed yet")]
+    CardNotRevealed,
+
+    #[msg("Submitted cards do not match the deck commitment")]
+    DeckCommitmentMismatch,
+
+    #[msg("Show or muck must be declared once per game")]
+    InvalidRevealChoice,
+
+    #[msg("Hand was not shown at showdown")]
+    HandNotShown,
+
+    #[msg("Invalid table configuration")]
+    InvalidTableConfig,
+
+    #[msg("Vault holds less than the amount being paid out")]
+    VaultUnderfunded,
+
+    #[msg("Table id already used by this creator")]
+    TableIdTaken,
+
+    #[msg("Card offset does not match the recorded slot hash")]
+    OffsetMismatch,
+
+    #[msg("Table is paused")]
+    TablePaused,
+
+    #[msg("No pending reveal request from this requester")]
+    NoRevealRequest,
+
+    #[msg("Rake referrer account missing")]
+    MissingReferrerAccount,
+
+    #[msg("Unsupported Inco input type")]
+    InvalidInputType,
+
+    #[msg("Unknown deck type")]
+    InvalidDeckType,
+
+    #[msg("Card values have not been attested")]
+    CardsNotAttested,
+
+    #[msg("A reveal request is already pending for this seat")]
+    RevealAlreadyRequested,
+
+    #[msg("Hands must be ranked before a contested pot is settled")]
+    HandsNotRanked,
 }
@@ -0,0 +1,24 @@
+use crate::error::PokerError;
+use crate::poker_hand::{card_in_deck, evaluate_7card_for};
+use crate::state::DeckType;
+use anchor_lang::prelude::*;
+
+/// Score seven cards under a deck's rankings, returned via return data
+///
+/// Same evaluator rank_hands uses, so clients can show a hand's strength
+/// or check a ranking without a game. deck_type: 0 = standard, 1 = short deck.
+pub fn handler(_ctx: Context<EvaluateHand>, cards: [u8; 7], deck_type: u8) -> Result<u64> {
+    let deck_type = DeckType::try_from(deck_type)?;
+
+    let mut seen_cards: u64 = 0;
+    for &card in cards.iter() {
+        require!(card_in_deck(card, deck_type), PokerError::InvalidCard);
+        require!(seen_cards & (1u64 << card) == 0, PokerError::DuplicateCard);
+        seen_cards |= 1u64 << card;
+    }
+
+    Ok(evaluate_7card_for(cards, deck_type) as u64)
+}
+
+#[derive(Accounts)]
+pub struct EvaluateHand {}
@@ -0,0 +1,137 @@
+use crate::state::GameStage;
+use anchor_lang::prelude::*;
+
+/// Blinds collected from the small and big blind seats at game start
+#[event]
+pub struct BlindsPosted {
+    pub game: Pubkey,
+    pub sb_seat: u8,
+    pub bb_seat: u8,
+    pub sb: u64,
+    pub bb: u64,
+}
+
+/// Game moved from one stage to another
+#[event]
+pub struct StageAdvanced {
+    pub game: Pubkey,
+    pub from: GameStage,
+    pub to: GameStage,
+}
+
+/// All cards submitted; commitment to the submitted ciphertexts for auditing
+#[event]
+pub struct DeckCommitted {
+    pub game: Pubkey,
+    pub commitment: [u8; 32],
+}
+
+/// A seat was granted decrypt access to its hole cards
+#[event]
+pub struct CardsDealt {
+    pub game: Pubkey,
+    pub seat: u8,
+}
+
+/// Game settled and the pot paid out to the winning seats
+#[event]
+pub struct HandSettled {
+    pub game: Pubkey,
+    pub winners: Vec<u8>,
+    pub pot: u64,
+    pub rake: u64,
+}
+
+/// Table backend authority replaced by the admin
+#[event]
+pub struct BackendRotated {
+    pub table: Pubkey,
+    pub old_backend: Pubkey,
+    pub new_backend: Pubkey,
+}
+
+/// Admin proposed handing the table to a new admin
+#[event]
+pub struct AdminTransferProposed {
+    pub table: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+/// Proposed admin accepted control of the table
+#[event]
+pub struct AdminTransferred {
+    pub table: Pubkey,
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+/// Admin paused or unpaused the table
+#[event]
+pub struct TablePauseChanged {
+    pub table: Pubkey,
+    pub paused: bool,
+}
+
+/// Admin changed the table's stakes between hands
+#[event]
+pub struct BlindsChanged {
+    pub table: Pubkey,
+    pub small_blind: u64,
+    pub big_blind: u64,
+}
+
+/// Someone asked a seat to reveal its hand
+#[event]
+pub struct RevealRequested {
+    pub game: Pubkey,
+    pub seat_index: u8,
+    pub requester: Pubkey,
+}
+
+/// A seat's owner approved a reveal request and the requester was allowed
+#[event]
+pub struct RevealApproved {
+    pub game: Pubkey,
+    pub seat_index: u8,
+    pub requester: Pubkey,
+}
+
+/// A seat's owner turned down a reveal request; nothing was opened
+#[event]
+pub struct RevealDeclined {
+    pub game: Pubkey,
+    pub seat_index: u8,
+    pub requester: Pubkey,
+}
+
+/// Why a seat's chip stack changed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StackChangeReason {
+    /// join_table / join_table_seat
+    BuyIn,
+    /// add_chips
+    TopUp,
+    /// Small or big blind posted in start_game
+    Blind,
+    /// Blind handed back by cancel_game
+    BlindReturned,
+    /// Stack paid out by refund_all
+    Refund,
+    /// withdraw_winnings
+    Withdrawal,
+}
+
+/// A seat's chip stack changed; indexers can total stacks per player from these
+///
+/// Pot winnings are paid straight to wallets and reported in HandSettled.
+#[event]
+pub struct StackChanged {
+    /// Game the change belongs to (None for joins and top-ups)
+    pub game: Option<Pubkey>,
+    pub seat: u8,
+    pub player: Pubkey,
+    pub chips_before: u64,
+    pub chips_after: u64,
+    pub reason: StackChangeReason,
+}
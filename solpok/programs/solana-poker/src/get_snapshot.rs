@@ -0,0 +1,50 @@
+use crate::error::PokerError;
+use crate::state::{GameStage, PokerGame, PokerTable};
+use anchor_lang::prelude::*;
+
+/// One-shot summary of a game for clients to render
+///
+/// Only on-chain state: betting (current bet, action, folds, all-ins) is
+/// tracked by the backend and isn't part of PokerGame.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct GameSnapshot {
+    pub stage: GameStage,
+    pub pot: u64,
+    pub dealer_seat: u8,
+    pub player_count: u8,
+    pub cards_processed: bool,
+    /// Community cards opened so far (bit i = community card i)
+    pub community_revealed: u8,
+    /// Seats ranked by rank_hands (bit i = seat i)
+    pub ranked_mask: u8,
+    /// Seats holding the best ranked hand (bit i = seat i)
+    pub winners_mask: u8,
+    pub waiting_deadline: i64,
+}
+
+/// Read-only view of a game, returned via return data (simulate to read it)
+pub fn handler(ctx: Context<GetSnapshot>) -> Result<GameSnapshot> {
+    let game = &ctx.accounts.game;
+
+    Ok(GameSnapshot {
+        stage: game.stage,
+        pot: game.pot,
+        dealer_seat: game.dealer_seat,
+        player_count: game.player_count,
+        cards_processed: game.cards_processed,
+        community_revealed: game.community_revealed,
+        ranked_mask: game.ranked_mask,
+        winners_mask: game.winners_mask,
+        waiting_deadline: game.waiting_deadline,
+    })
+}
+
+#[derive(Accounts)]
+pub struct GetSnapshot<'info> {
+    pub table: Account<'info, PokerTable>,
+
+    #[account(
+        constraint = game.table == table.key() @ PokerError::NoActiveGame
+    )]
+    pub game: Account<'info, PokerGame>,
+}
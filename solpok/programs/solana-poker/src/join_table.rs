@@ -1,14 +1,31 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program;
-use crate::state::{PokerTable, PlayerSeat};
+use anchor_spl::token::{Token, TokenAccount};
+use crate::state::{PokerTable, PlayerSeat, RevealChoice};
 use crate::error::PokerError;
+use crate::events::{StackChangeReason, StackChanged};
+use crate::vault;
 
-/// Player joins a table with a buy-in
+/// Player joins a table with a buy-in, taking the lowest free seat
 pub fn handler(ctx: Context<JoinTable>, buy_in: u64) -> Result<()> {
+    let table = &ctx.accounts.table;
+    let seat_index = (0..table.max_players)
+        .find(|&seat| table.seats_taken & (1 << seat) == 0)
+        .ok_or(PokerError::TableFull)?;
+    seat_player(ctx, buy_in, seat_index)
+}
+
+/// Seat the player at `seat_index`, shared by join_table and join_table_seat
+pub(crate) fn seat_player(ctx: Context<JoinTable>, buy_in: u64, seat_index: u8) -> Result<()> {
     let table = &mut ctx.accounts.table;
     let player_seat = &mut ctx.accounts.player_seat;
-    
-    // Validate buy-in amount
+
+    // A freshly created seat is zeroed; anything else is an existing seat
+    // (e.g. a retried join that already landed)
+    require!(player_seat.player == Pubkey::default(), PokerError::SeatTaken);
+
+    // Validate buy-in amount; a seat must never start with zero chips,
+    // whatever buy_in_min the table was created with
+    require!(buy_in > 0, PokerError::InvalidBuyIn);
     require!(
         buy_in >= table.buy_in_min && buy_in <= table.buy_in_max,
         PokerError::InvalidBuyIn
@@ -16,26 +33,31 @@ pub fn handler(ctx: Context<JoinTable>, buy_in: u64) -> Result<()> {
     
     // Check table isn't full
     require!(table.player_count < table.max_players, PokerError::TableFull);
+    require!(seat_index < table.max_players, PokerError::InvalidSeatIndex);
+    require!(
+        table.seats_taken & (1 << seat_index) == 0,
+        PokerError::SeatTaken
+    );
     
     // Check no game in progress
     require!(table.current_game.is_none(), PokerError::GameInProgress);
 
-    // Transfer SOL from player to vault
-    system_program::transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            system_program::Transfer {
-                from: ctx.accounts.player.to_account_info(),
-                to: ctx.accounts.vault.to_account_info(),
-            },
-        ),
+    // Transfer SOL (or SPL tokens on token tables) from player to vault
+    vault::deposit(
+        table.token_mint,
+        &ctx.accounts.vault.to_account_info(),
+        &ctx.accounts.player.to_account_info(),
+        &ctx.accounts.player_token_account,
+        &ctx.accounts.vault_token_account,
+        &ctx.accounts.token_program,
+        &ctx.accounts.system_program,
         buy_in,
     )?;
 
     // Initialize PlayerSeat
     player_seat.game = Pubkey::default(); // Will be set/used later
     player_seat.player = ctx.accounts.player.key();
-    player_seat.seat_index = table.player_count; // Assign next available seat
+    player_seat.seat_index = seat_index;
     player_seat.chips = buy_in;
     player_seat.current_bet = 0;
     player_seat.total_bet = 0;
@@ -43,9 +65,22 @@ pub fn handler(ctx: Context<JoinTable>, buy_in: u64) -> Result<()> {
     player_seat.is_all_in = false;
     player_seat.has_acted = false;
     player_seat.hand_rank = 0;
+    player_seat.reveal_choice = RevealChoice::Undecided;
+    player_seat.reveal_requested_by = None;
+    player_seat.reveal_request_game = Pubkey::default();
     player_seat.bump = ctx.bumps.player_seat;
 
     table.player_count += 1;
+    table.seats_taken |= 1 << seat_index;
+
+    emit!(StackChanged {
+        game: None,
+        seat: seat_index,
+        player: player_seat.player,
+        chips_before: 0,
+        chips_after: buy_in,
+        reason: StackChangeReason::BuyIn,
+    });
 
     msg!(
         "Player {} joined table at seat {} with {} lamports. Players: {}/{}",
@@ -61,19 +96,22 @@ pub fn handler(ctx: Context<JoinTable>, buy_in: u64) -> Result<()> {
 
 #[derive(Accounts)]
 pub struct JoinTable<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = !table.paused @ PokerError::TablePaused
+    )]
     pub table: Account<'info, PokerTable>,
 
     /// CHECK: Vault PDA to receive SOL
     #[account(
         mut,
         seeds = [b"vault", table.key().as_ref()],
-        bump
+        bump = table.vault_bump
     )]
     pub vault: AccountInfo<'info>,
 
     #[account(
-        init,
+        init_if_needed,
         payer = player,
         space = PlayerSeat::LEN,
         seeds = [b"player_seat", table.key().as_ref(), player.key().as_ref()],
@@ -84,5 +122,15 @@ pub struct JoinTable<'info> {
     #[account(mut)]
     pub player: Signer<'info>,
 
+    /// Player's token account for the table mint (token tables only)
+    #[account(mut)]
+    pub player_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Vault-owned token account for the table mint (token tables only)
+    #[account(mut)]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
     pub system_program: Program<'info, System>,
 }
@@ -0,0 +1,10 @@
+use crate::join_table::{self, JoinTable};
+use anchor_lang::prelude::*;
+
+/// Player joins a table with a buy-in at a chosen seat
+///
+/// Same accounts and checks as join_table. Fails with SeatTaken if the
+/// seat is occupied, or InvalidSeatIndex if it is beyond max_players.
+pub fn handler(ctx: Context<JoinTable>, buy_in: u64, seat_index: u8) -> Result<()> {
+    join_table::seat_player(ctx, buy_in, seat_index)
+}
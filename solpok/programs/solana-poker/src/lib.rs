@@ -4,31 +4,90 @@ use anchor_lang::prelude::*;
 
 pub mod constants;
 pub mod error;
+pub mod events;
+pub mod poker_hand;
 pub mod state;
+pub mod vault;
 
 pub mod create_table;
+#[cfg(feature = "dev-reveal")]
+pub mod dev_reveal_pool;
+pub mod close_table;
+pub mod declare_showdown_action;
+pub mod set_admin;
+pub mod accept_admin;
+pub mod set_backend;
+pub mod pause_table;
+pub mod set_blinds;
 pub mod allow_random;
+pub mod allow_spectator;
+pub mod allow_shown_hand;
+pub mod request_reveal;
+pub mod approve_reveal;
+pub mod decline_reveal;
 pub mod generate_random;
 pub mod join_table;
+pub mod join_table_seat;
+pub mod add_chips;
+pub mod withdraw_winnings;
+pub mod batch_allow_hole_cards;
+pub mod cancel_game;
 pub mod refund_all;
 pub mod process_cards;
+pub mod attest_cards;
+pub mod rank_hands;
 pub mod reveal_card_offset;
 pub mod reveal_community;
 pub mod reveal_hand;
+pub mod reveal_street;
 pub mod settle_game;
 pub mod start_game;
+pub mod start_next_game;
+pub mod verify_deck_commitment;
+pub mod verify_offset;
+pub mod get_snapshot;
+pub mod evaluate_hand;
+pub mod withdraw_rake;
 
 use create_table::*;
+#[cfg(feature = "dev-reveal")]
+use dev_reveal_pool::*;
+use close_table::*;
+use declare_showdown_action::*;
+use set_admin::*;
+use accept_admin::*;
+use set_backend::*;
+use pause_table::*;
+use set_blinds::*;
 use allow_random::*;
+use allow_spectator::*;
+use allow_shown_hand::*;
+use request_reveal::*;
+use approve_reveal::*;
+use decline_reveal::*;
 use generate_random::*;
 use join_table::*;
+use add_chips::*;
+use withdraw_winnings::*;
+use batch_allow_hole_cards::*;
+use cancel_game::*;
 use refund_all::*;
 use process_cards::*;
+use attest_cards::*;
+use rank_hands::*;
 use reveal_card_offset::*;
 use reveal_community::*;
 use reveal_hand::*;
+use reveal_street::*;
 use settle_game::*;
 use start_game::*;
+use start_next_game::*;
+use verify_deck_commitment::*;
+use verify_offset::*;
+use get_snapshot::*;
+use evaluate_hand::*;
+use withdraw_rake::*;
+use state::{RevealChoice, Street};
 
 declare_id!("7EZ1zWNMjuHh62dikk9TAo478VMzAiLkvg8S7Vm85T7s");
 
@@ -37,6 +96,12 @@ pub mod solana_poker {
     use super::*;
 
     /// Creates a new poker table
+    ///
+    /// Pass a token_mint to run the table on an SPL token (e.g. USDC)
+    /// instead of native SOL. rake_bps/rake_cap configure the rake taken
+    /// from each pot that sees a flop; an optional rake_referrer receives
+    /// referrer_share_bps of it.
+    #[allow(clippy::too_many_arguments)]
     pub fn create_table(
         ctx: Context<CreateTable>,
         table_id: u64,
@@ -44,7 +109,13 @@ pub mod solana_poker {
         buy_in_min: u64,
         buy_in_max: u64,
         small_blind: u64,
+        big_blind: u64,
         backend_account: Pubkey,
+        token_mint: Option<Pubkey>,
+        rake_bps: u16,
+        rake_cap: u64,
+        rake_referrer: Option<Pubkey>,
+        referrer_share_bps: u16,
     ) -> Result<()> {
         create_table::handler(
             ctx,
@@ -53,15 +124,81 @@ pub mod solana_poker {
             buy_in_min,
             buy_in_max,
             small_blind,
+            big_blind,
             backend_account,
+            token_mint,
+            rake_bps,
+            rake_cap,
+            rake_referrer,
+            referrer_share_bps,
         )
     }
 
+    /// Creator closes an empty table and reclaims its rent and vault balance
+    pub fn close_table(ctx: Context<CloseTable>) -> Result<()> {
+        close_table::handler(ctx)
+    }
+
+    /// Admin rotates the table's backend authority
+    pub fn set_backend(ctx: Context<SetBackend>, new_backend: Pubkey) -> Result<()> {
+        set_backend::handler(ctx, new_backend)
+    }
+
+    /// Admin freezes this table (not the whole program) during an incident;
+    /// payouts and refunds still work
+    pub fn pause_table(ctx: Context<PauseTable>) -> Result<()> {
+        pause_table::handler(ctx, true)
+    }
+
+    /// Admin lifts a pause
+    pub fn unpause_table(ctx: Context<PauseTable>) -> Result<()> {
+        pause_table::handler(ctx, false)
+    }
+
+    /// Admin changes the blinds between hands (big_blind = 0 uses the standard multiplier)
+    pub fn set_blinds(ctx: Context<SetBlinds>, small_blind: u64, big_blind: u64) -> Result<()> {
+        set_blinds::handler(ctx, small_blind, big_blind)
+    }
+
+    /// Admin proposes a new admin (takes effect on accept_admin)
+    pub fn set_admin(ctx: Context<SetAdmin>, new_admin: Pubkey) -> Result<()> {
+        set_admin::handler(ctx, new_admin)
+    }
+
+    /// Proposed admin accepts control of the table
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        accept_admin::handler(ctx)
+    }
+
     /// Player joins a table with a buy-in
     pub fn join_table(ctx: Context<JoinTable>, buy_in: u64) -> Result<()> {
         join_table::handler(ctx, buy_in)
     }
 
+    /// Player joins a table with a buy-in at a specific seat index
+    pub fn join_table_seat(ctx: Context<JoinTable>, buy_in: u64, seat_index: u8) -> Result<()> {
+        join_table_seat::handler(ctx, buy_in, seat_index)
+    }
+
+    /// Seated player tops up their chips between games (up to buy_in_max)
+    pub fn add_chips(ctx: Context<AddChips>, amount: u64) -> Result<()> {
+        add_chips::handler(ctx, amount)
+    }
+
+    /// Seated player withdraws chips between games, keeping at least buy_in_min
+    /// (co-signed by the backend)
+    pub fn withdraw_winnings(ctx: Context<WithdrawWinnings>, amount: u64) -> Result<()> {
+        withdraw_winnings::handler(ctx, amount)
+    }
+
+    /// Cancel a game stuck in Waiting, refunding and unseating the blind posters
+    /// (admin any time, anyone after the waiting deadline)
+    pub fn cancel_game<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelGame<'info>>,
+    ) -> Result<()> {
+        cancel_game::handler(ctx)
+    }
+
     /// Backend refunds all players and clears table
     pub fn refund_all<'info>(
         ctx: Context<'_, '_, 'info, 'info, RefundAll<'info>>,
@@ -72,21 +209,27 @@ pub mod solana_poker {
     /// Admin starts a new game with blind bets
     ///
     /// Pass small_blind and big_blind seat accounts via remaining_accounts
-    /// to collect blind bets at game start.
+    /// to collect the table's blinds at game start. deck_type: 0 = standard,
+    /// 1 = short deck (6+).
     pub fn start_game<'info>(
         ctx: Context<'_, '_, 'info, 'info, StartGame<'info>>,
         game_id: u64,
         backend_account: Pubkey,
-        small_blind_amount: u64,
-        big_blind_amount: u64,
+        deck_type: u8,
     ) -> Result<()> {
-        start_game::handler(
-            ctx,
-            game_id,
-            backend_account,
-            small_blind_amount,
-            big_blind_amount,
-        )
+        start_game::handler(ctx, game_id, backend_account, deck_type)
+    }
+
+    /// Backend starts the table's next game without choosing an id
+    ///
+    /// game_id is taken from table.games_played, so the game PDA is
+    /// [b"game", table, games_played]. Blinds work as in start_game.
+    pub fn start_next_game<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StartNextGame<'info>>,
+        backend_account: Pubkey,
+        deck_type: u8,
+    ) -> Result<()> {
+        start_next_game::handler(ctx, backend_account, deck_type)
     }
 
     /// Process cards in mini-batches (2 cards per batch, 8 batches total)
@@ -106,11 +249,49 @@ pub mod solana_poker {
         process_cards::handler(ctx, batch_index, card_0, card_1, input_type)
     }
 
+    /// Check replayed batch hashes against the game's deck commitment
+    pub fn verify_deck_commitment(
+        ctx: Context<VerifyDeckCommitment>,
+        batch_hashes: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        verify_deck_commitment::handler(ctx, batch_hashes)
+    }
+
+    /// Recompute the card offset from the recorded slot hash and check it
+    pub fn verify_offset(ctx: Context<VerifyOffset>, claimed_offset: u8) -> Result<()> {
+        verify_offset::handler(ctx, claimed_offset)
+    }
+
+    /// Read-only summary of a game in one call (returned via return data)
+    pub fn get_snapshot(ctx: Context<GetSnapshot>) -> Result<GameSnapshot> {
+        get_snapshot::handler(ctx)
+    }
+
+    /// Score seven card indices under a deck's rankings (returned via return data)
+    pub fn evaluate_hand(ctx: Context<EvaluateHand>, cards: [u8; 7], deck_type: u8) -> Result<u64> {
+        evaluate_hand::handler(ctx, cards, deck_type)
+    }
+
+    /// DEV ONLY (`dev-reveal` feature): open every card in the game to the admin
+    #[cfg(feature = "dev-reveal")]
+    pub fn dev_reveal_pool<'info>(
+        ctx: Context<'_, '_, '_, 'info, DevRevealPool<'info>>,
+    ) -> Result<()> {
+        dev_reveal_pool::handler(ctx)
+    }
+
     /// Player reveals their hand (grants decrypt access to themselves)
     pub fn reveal_hand<'info>(ctx: Context<'_, '_, '_, 'info, RevealHand<'info>>) -> Result<()> {
         reveal_hand::handler(ctx)
     }
 
+    /// Backend grants hole-card decrypt access to a slice of seats in one call
+    pub fn batch_allow_hole_cards<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchAllowHoleCards<'info>>,
+    ) -> Result<()> {
+        batch_allow_hole_cards::handler(ctx)
+    }
+
     /// Admin allows a player to decrypt the card_offset handle
     /// Used to verify the offset value (0-51)
     pub fn reveal_card_offset<'info>(
@@ -129,16 +310,95 @@ pub mod solana_poker {
         reveal_community::handler(ctx)
     }
 
-    /// Settle the game and pay the winner
+    /// Record decrypted card values verified against their Inco handles
+    ///
+    /// The transaction must include the ed25519 instructions from Inco's
+    /// attested decrypt of the cards at `slots` (0-9 hole, 10-14 community).
+    pub fn attest_cards(
+        ctx: Context<AttestCards>,
+        slots: Vec<u8>,
+        plaintexts: Vec<Vec<u8>>,
+    ) -> Result<()> {
+        attest_cards::handler(ctx, slots, plaintexts)
+    }
+
+    /// Backend ranks each showdown hand on-chain from the attested cards
+    ///
+    /// Pass the seats being ranked via remaining_accounts. The community
+    /// cards and each seat's hole cards must have been through attest_cards.
+    pub fn rank_hands<'info>(ctx: Context<'_, '_, 'info, 'info, RankHands<'info>>) -> Result<()> {
+        rank_hands::handler(ctx)
+    }
+
+    /// Backend opens the flop, turn or river to seated players
+    ///
+    /// Pass [player_seat, player, allowance per card] groups via remaining_accounts.
+    pub fn reveal_street<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RevealStreet<'info>>,
+        street: Street,
+    ) -> Result<()> {
+        reveal_street::handler(ctx, street)
+    }
+
+    /// Backend lets a spectator decrypt a street that is already revealed
+    ///
+    /// Pass one allowance account per card on the street via remaining_accounts.
+    pub fn allow_spectator<'info>(
+        ctx: Context<'_, '_, '_, 'info, AllowSpectator<'info>>,
+        street: Street,
+    ) -> Result<()> {
+        allow_spectator::handler(ctx, street)
+    }
+
+    /// Player declares show or muck at showdown
+    pub fn declare_showdown_action(
+        ctx: Context<DeclareShowdownAction>,
+        choice: RevealChoice,
+    ) -> Result<()> {
+        declare_showdown_action::handler(ctx, choice)
+    }
+
+    /// Backend lets a spectator decrypt a hand that was shown at showdown
+    pub fn allow_shown_hand<'info>(
+        ctx: Context<'_, '_, '_, 'info, AllowShownHand<'info>>,
+    ) -> Result<()> {
+        allow_shown_hand::handler(ctx)
+    }
+
+    /// Ask a seat to reveal its hand (needs the owner's approve_reveal)
+    pub fn request_reveal(ctx: Context<RequestReveal>) -> Result<()> {
+        request_reveal::handler(ctx)
+    }
+
+    /// Seat owner approves a reveal request, opening their hand to the requester
+    pub fn approve_reveal<'info>(
+        ctx: Context<'_, '_, '_, 'info, ApproveReveal<'info>>,
+    ) -> Result<()> {
+        approve_reveal::handler(ctx)
+    }
+
+    /// Seat owner turns down a pending reveal request, freeing the seat for another
+    pub fn decline_reveal(ctx: Context<DeclineReveal>) -> Result<()> {
+        decline_reveal::handler(ctx)
+    }
+
+    /// Settle the game and pay the winners
     ///
     /// Called by backend after off-chain gameplay completes.
-    /// Transfers final_pot from vault to winner's wallet.
-    pub fn settle_game(
-        ctx: Context<SettleGame>,
-        winner_seat_index: u8,
+    /// Splits final_pot evenly between the winning seats (odd chips go to
+    /// the winner closest to the dealer's left).
+    /// Pass [player_seat, player_wallet] pairs for each winner via remaining_accounts.
+    pub fn settle_game<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleGame<'info>>,
+        winner_seat_indices: Vec<u8>,
         final_pot: u64,
     ) -> Result<()> {
-        settle_game::handler(ctx, winner_seat_index, final_pot)
+        settle_game::handler(ctx, winner_seat_indices, final_pot)
+    }
+
+    /// Creator withdraws the rake collected in the table's rake vault
+    pub fn withdraw_rake(ctx: Context<WithdrawRake>) -> Result<()> {
+        withdraw_rake::handler(ctx)
     }
 
     /// Generate an encrypted random number using Inco e_rand
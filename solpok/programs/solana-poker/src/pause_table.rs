@@ -0,0 +1,41 @@
+use crate::error::PokerError;
+use crate::events::TablePauseChanged;
+use crate::state::PokerTable;
+use anchor_lang::prelude::*;
+
+/// Admin pauses or unpauses the table
+///
+/// The switch is per table: it only affects this PokerTable, and other
+/// tables (even the same admin's) keep running. There is no program-wide
+/// pause; an operator freezes each affected table in turn.
+///
+/// While paused, join_table, join_table_seat, add_chips, start_game,
+/// start_next_game, process_cards_batch, attest_cards, reveal_street and
+/// rank_hands are refused. Paths that return funds (settle_game,
+/// cancel_game, refund_all, withdraw_winnings, withdraw_rake, close_table)
+/// stay open so nothing is trapped in the vault during an incident.
+pub fn handler(ctx: Context<PauseTable>, paused: bool) -> Result<()> {
+    let table = &mut ctx.accounts.table;
+
+    table.paused = paused;
+
+    msg!("Table {} paused: {}", table.table_id, paused);
+
+    emit!(TablePauseChanged {
+        table: table.key(),
+        paused,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct PauseTable<'info> {
+    #[account(
+        mut,
+        has_one = creator @ PokerError::NotAdmin
+    )]
+    pub table: Account<'info, PokerTable>,
+
+    pub creator: Signer<'info>,
+}
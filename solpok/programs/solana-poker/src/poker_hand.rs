@@ -0,0 +1,352 @@
+use crate::state::DeckType;
+
+/// Comparable hand strength: higher is better
+///
+/// Layout: category in bits 20..24, then five 4-bit rank slots (most
+/// significant first) holding the ranks that decide ties within the category.
+///
+/// Every kicker that can matter is packed, so plain integer comparison is the
+/// whole tie-break: KK-A-9-4 beats KK-Q-J-T, KK-QQ-7 beats KK-QQ-5, and hands
+/// that only differ by suit compare equal (a true chop in winners_mask).
+pub type HandRank = u32;
+
+/// Number of distinct cards in a standard deck
+pub const DECK_SIZE: u8 = 52;
+
+/// Lowest rank left in a short deck (4 = six)
+pub const SHORT_DECK_LOWEST_RANK: u8 = 4;
+
+/// Hand categories, weakest to strongest
+pub const HIGH_CARD: u32 = 0;
+pub const ONE_PAIR: u32 = 1;
+pub const TWO_PAIR: u32 = 2;
+pub const THREE_OF_A_KIND: u32 = 3;
+pub const STRAIGHT: u32 = 4;
+pub const FLUSH: u32 = 5;
+pub const FULL_HOUSE: u32 = 6;
+pub const FOUR_OF_A_KIND: u32 = 7;
+pub const STRAIGHT_FLUSH: u32 = 8;
+
+/// Rank of a card index (0 = deuce ... 12 = ace)
+///
+/// Cards are indexed 0-51 as `suit * 13 + rank`, which is the decrypted
+/// card plaintext reduced mod 52.
+pub fn card_rank(card: u8) -> u8 {
+    card % 13
+}
+
+/// Suit of a card index (0-3)
+pub fn card_suit(card: u8) -> u8 {
+    card / 13
+}
+
+/// Whether a card index belongs to the given deck
+pub fn card_in_deck(card: u8, deck_type: DeckType) -> bool {
+    match deck_type {
+        DeckType::Standard => card < DECK_SIZE,
+        DeckType::ShortDeck => card < DECK_SIZE && card_rank(card) >= SHORT_DECK_LOWEST_RANK,
+    }
+}
+
+/// Category stored in the top bits of a HandRank
+pub fn hand_category(rank: HandRank) -> u32 {
+    rank >> 20
+}
+
+/// Score the best 5-card hand out of two hole cards plus five community cards
+///
+/// Card indices must be < DECK_SIZE.
+pub fn evaluate_7card(cards: [u8; 7]) -> HandRank {
+    evaluate_7card_for(cards, DeckType::Standard)
+}
+
+/// evaluate_7card under the given deck's rankings
+///
+/// Short deck: A-6-7-8-9 is the lowest straight (nine-high), and a flush
+/// is stored with FULL_HOUSE's category and a full house with FLUSH's, so
+/// integer comparison puts the flush on top. Seven cards can't hold both,
+/// so swapping the categories is the whole reordering.
+pub fn evaluate_7card_for(cards: [u8; 7], deck_type: DeckType) -> HandRank {
+    let (flush, full_house) = match deck_type {
+        DeckType::Standard => (FLUSH, FULL_HOUSE),
+        DeckType::ShortDeck => (FULL_HOUSE, FLUSH),
+    };
+
+    let mut rank_counts = [0u8; 13];
+    let mut suit_masks = [0u16; 4];
+    let mut rank_mask: u16 = 0;
+
+    for &card in cards.iter() {
+        let rank = card_rank(card);
+        rank_counts[rank as usize] += 1;
+        suit_masks[card_suit(card) as usize] |= 1 << rank;
+        rank_mask |= 1 << rank;
+    }
+
+    // Straight flush / flush
+    if let Some(flush_mask) = suit_masks.iter().find(|m| m.count_ones() >= 5) {
+        if let Some(high) = straight_high(*flush_mask, deck_type) {
+            return pack(STRAIGHT_FLUSH, &[high]);
+        }
+        return pack(flush, &top_ranks(*flush_mask, 5));
+    }
+
+    // Ranks grouped by multiplicity, highest rank first
+    let mut quads = Vec::with_capacity(1);
+    let mut trips = Vec::with_capacity(2);
+    let mut pairs = Vec::with_capacity(3);
+    for rank in (0..13u8).rev() {
+        match rank_counts[rank as usize] {
+            4 => quads.push(rank),
+            3 => trips.push(rank),
+            2 => pairs.push(rank),
+            _ => {}
+        }
+    }
+
+    if let Some(&quad) = quads.first() {
+        let kicker = top_ranks(rank_mask & !(1 << quad), 1);
+        return pack(FOUR_OF_A_KIND, &[quad, kicker[0]]);
+    }
+
+    if let Some(&trip) = trips.first() {
+        // Second set of trips plays as the pair
+        let pair = trips.get(1).copied().into_iter().chain(pairs.first().copied()).max();
+        if let Some(pair) = pair {
+            return pack(full_house, &[trip, pair]);
+        }
+    }
+
+    if let Some(high) = straight_high(rank_mask, deck_type) {
+        return pack(STRAIGHT, &[high]);
+    }
+
+    if let Some(&trip) = trips.first() {
+        let kickers = top_ranks(rank_mask & !(1 << trip), 2);
+        return pack(THREE_OF_A_KIND, &[trip, kickers[0], kickers[1]]);
+    }
+
+    if pairs.len() >= 2 {
+        let (high, low) = (pairs[0], pairs[1]);
+        let kicker = top_ranks(rank_mask & !(1 << high) & !(1 << low), 1);
+        return pack(TWO_PAIR, &[high, low, kicker[0]]);
+    }
+
+    if let Some(&pair) = pairs.first() {
+        let kickers = top_ranks(rank_mask & !(1 << pair), 3);
+        return pack(ONE_PAIR, &[pair, kickers[0], kickers[1], kickers[2]]);
+    }
+
+    pack(HIGH_CARD, &top_ranks(rank_mask, 5))
+}
+
+/// Highest card of the best straight in a rank bitmask, if any
+/// (the wheel A-2-3-4-5 counts as five-high, or A-6-7-8-9 as nine-high
+/// in a short deck)
+fn straight_high(mask: u16, deck_type: DeckType) -> Option<u8> {
+    for high in (4..13u8).rev() {
+        let run: u16 = 0b1_1111 << (high - 4);
+        if mask & run == run {
+            return Some(high);
+        }
+    }
+    let (wheel, wheel_high): (u16, u8) = match deck_type {
+        DeckType::Standard => ((1 << 12) | 0b1111, 3),
+        DeckType::ShortDeck => ((1 << 12) | (0b1111 << SHORT_DECK_LOWEST_RANK), 7),
+    };
+    if mask & wheel == wheel {
+        return Some(wheel_high);
+    }
+    None
+}
+
+/// The `count` highest ranks set in a rank bitmask, highest first
+fn top_ranks(mask: u16, count: usize) -> Vec<u8> {
+    (0..13u8)
+        .rev()
+        .filter(|rank| mask & (1 << rank) != 0)
+        .take(count)
+        .collect()
+}
+
+/// Pack a category and its deciding ranks into a HandRank
+fn pack(category: u32, ranks: &[u8]) -> HandRank {
+    let mut value = category;
+    for slot in 0..5 {
+        let rank = ranks.get(slot).copied().unwrap_or(0) as u32;
+        value = (value << 4) | rank;
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Card index from a rank (2-14, ace high) and a suit (0-3)
+    fn card(rank: u8, suit: u8) -> u8 {
+        suit * 13 + (rank - 2)
+    }
+
+    fn score(cards: [(u8, u8); 7]) -> HandRank {
+        evaluate_7card(cards.map(|(rank, suit)| card(rank, suit)))
+    }
+
+    fn score_short(cards: [(u8, u8); 7]) -> HandRank {
+        evaluate_7card_for(cards.map(|(rank, suit)| card(rank, suit)), DeckType::ShortDeck)
+    }
+
+    const HIGH: [(u8, u8); 7] = [(14, 0), (13, 1), (9, 2), (7, 3), (4, 0), (3, 1), (2, 2)];
+    const PAIR: [(u8, u8); 7] = [(13, 0), (13, 1), (9, 2), (7, 3), (4, 0), (3, 1), (2, 2)];
+    const TWO_PAIRS: [(u8, u8); 7] = [(13, 0), (13, 1), (9, 2), (9, 3), (4, 0), (3, 1), (2, 2)];
+    const TRIPS: [(u8, u8); 7] = [(13, 0), (13, 1), (13, 2), (9, 3), (4, 0), (3, 1), (2, 2)];
+    const WHEEL: [(u8, u8); 7] = [(14, 0), (2, 1), (3, 2), (4, 3), (5, 0), (9, 1), (13, 2)];
+    const TEN_HIGH_STRAIGHT: [(u8, u8); 7] = [(10, 0), (9, 1), (8, 2), (7, 3), (6, 0), (2, 1), (3, 2)];
+    const HEART_FLUSH: [(u8, u8); 7] = [(14, 1), (11, 1), (9, 1), (6, 1), (3, 1), (13, 0), (2, 2)];
+    const FULL_HOUSE_HAND: [(u8, u8); 7] = [(13, 0), (13, 1), (13, 2), (9, 3), (9, 0), (3, 1), (2, 2)];
+    const QUADS: [(u8, u8); 7] = [(13, 0), (13, 1), (13, 2), (13, 3), (9, 0), (3, 1), (2, 2)];
+    const NINE_HIGH_SF: [(u8, u8); 7] = [(9, 2), (8, 2), (7, 2), (6, 2), (5, 2), (13, 0), (2, 1)];
+
+    #[test]
+    fn every_category_is_recognised() {
+        let cases = [
+            (HIGH, HIGH_CARD),
+            (PAIR, ONE_PAIR),
+            (TWO_PAIRS, TWO_PAIR),
+            (TRIPS, THREE_OF_A_KIND),
+            (WHEEL, STRAIGHT),
+            (TEN_HIGH_STRAIGHT, STRAIGHT),
+            (HEART_FLUSH, FLUSH),
+            (FULL_HOUSE_HAND, FULL_HOUSE),
+            (QUADS, FOUR_OF_A_KIND),
+            (NINE_HIGH_SF, STRAIGHT_FLUSH),
+        ];
+        for (cards, expected) in cases {
+            assert_eq!(hand_category(score(cards)), expected, "{:?}", cards);
+        }
+    }
+
+    #[test]
+    fn categories_rank_in_order() {
+        let ladder = [
+            HIGH,
+            PAIR,
+            TWO_PAIRS,
+            TRIPS,
+            TEN_HIGH_STRAIGHT,
+            HEART_FLUSH,
+            FULL_HOUSE_HAND,
+            QUADS,
+            NINE_HIGH_SF,
+        ];
+        for pair in ladder.windows(2) {
+            assert!(score(pair[0]) < score(pair[1]), "{:?} vs {:?}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn wheel_is_five_high() {
+        let six_high = [(6, 1), (2, 1), (3, 2), (4, 3), (5, 0), (9, 1), (13, 2)];
+        assert_eq!(score(WHEEL), pack(STRAIGHT, &[3]));
+        assert!(score(WHEEL) < score(six_high));
+        assert!(score(WHEEL) > score(TRIPS));
+    }
+
+    #[test]
+    fn second_trips_plays_as_the_pair() {
+        let kings_full_of_nines = [(13, 0), (13, 1), (13, 2), (9, 3), (9, 0), (9, 1), (2, 2)];
+        assert_eq!(
+            score(kings_full_of_nines),
+            pack(FULL_HOUSE, &[card_rank(card(13, 0)), card_rank(card(9, 0))])
+        );
+    }
+
+    #[test]
+    fn ties_within_a_category_break_on_rank() {
+        // Higher straight
+        assert!(score(TEN_HIGH_STRAIGHT) > score(WHEEL));
+        // Flushes compare card by card: A-J-9-6-3 beats A-J-9-6-2
+        let lower_flush = [(14, 1), (11, 1), (9, 1), (6, 1), (2, 1), (13, 0), (3, 2)];
+        assert!(score(HEART_FLUSH) > score(lower_flush));
+        // Full houses compare trips first: 999-KK beats 888-AA
+        let nines_full = [(9, 0), (9, 1), (9, 2), (13, 3), (13, 0), (3, 1), (2, 2)];
+        let eights_full = [(8, 0), (8, 1), (8, 2), (14, 3), (14, 0), (3, 1), (2, 2)];
+        assert!(score(nines_full) > score(eights_full));
+        // Quads fall back to the kicker
+        let quads_ace_kicker = [(13, 0), (13, 1), (13, 2), (13, 3), (14, 0), (3, 1), (2, 2)];
+        assert!(score(quads_ace_kicker) > score(QUADS));
+    }
+
+    /// Score a player's hole cards against a shared board, as at showdown
+    fn showdown(board: [(u8, u8); 5], hole: [(u8, u8); 2]) -> HandRank {
+        score([hole[0], hole[1], board[0], board[1], board[2], board[3], board[4]])
+    }
+
+    #[test]
+    fn pair_with_the_higher_kicker_wins() {
+        // Board Kc Kh 9d 7s 3c: both hold kings, the kicker decides
+        let board = [(13, 0), (13, 1), (9, 2), (7, 3), (3, 0)];
+        let ace_kicker = showdown(board, [(14, 3), (2, 2)]);
+        let queen_kicker = showdown(board, [(12, 3), (2, 0)]);
+        assert_eq!(hand_category(ace_kicker), ONE_PAIR);
+        assert_eq!(hand_category(queen_kicker), ONE_PAIR);
+        assert!(ace_kicker > queen_kicker);
+        // Down to the last card: K-K-9-7-5 beats K-K-9-7-4
+        assert!(showdown(board, [(5, 3), (2, 2)]) > showdown(board, [(4, 3), (2, 0)]));
+    }
+
+    #[test]
+    fn two_pair_breaks_on_pairs_then_kicker() {
+        // Board Kc Kh 9d 9s 4c: same two pair, the kicker decides
+        let board = [(13, 0), (13, 1), (9, 2), (9, 3), (4, 0)];
+        let queen_kicker = showdown(board, [(12, 3), (2, 2)]);
+        let jack_kicker = showdown(board, [(11, 3), (3, 2)]);
+        assert_eq!(hand_category(queen_kicker), TWO_PAIR);
+        assert!(queen_kicker > jack_kicker);
+        // Board Kc Kh 8d 4s 2c: the second pair outranks any kicker
+        let board = [(13, 0), (13, 1), (8, 2), (4, 3), (2, 0)];
+        let kings_and_nines = showdown(board, [(9, 0), (9, 1)]);
+        let kings_and_eights_ace_kicker = showdown(board, [(8, 0), (14, 1)]);
+        assert_eq!(hand_category(kings_and_eights_ace_kicker), TWO_PAIR);
+        assert!(kings_and_nines > kings_and_eights_ace_kicker);
+    }
+
+    #[test]
+    fn identical_hands_chop() {
+        // Both play the board's broadway straight
+        let board = [(14, 0), (13, 1), (12, 2), (11, 3), (10, 0)];
+        assert_eq!(showdown(board, [(2, 1), (3, 2)]), showdown(board, [(2, 3), (4, 1)]));
+        // Kickers outside the best five don't count: both play K-K-Q-J-9
+        let board = [(13, 0), (13, 1), (12, 2), (11, 3), (9, 0)];
+        assert_eq!(showdown(board, [(8, 2), (2, 1)]), showdown(board, [(7, 2), (3, 1)]));
+        // Same ranks in different suits are equal
+        let board = [(13, 0), (13, 1), (9, 2), (7, 3), (3, 0)];
+        assert_eq!(showdown(board, [(14, 3), (2, 2)]), showdown(board, [(14, 2), (2, 3)]));
+    }
+
+    #[test]
+    fn short_deck_puts_flush_over_full_house() {
+        let flush = [(14, 0), (12, 0), (10, 0), (8, 0), (6, 0), (7, 1), (11, 2)];
+        let full_house = [(13, 0), (13, 1), (13, 2), (12, 0), (12, 1), (7, 3), (8, 2)];
+        assert!(score(full_house) > score(flush));
+        assert!(score_short(flush) > score_short(full_house));
+    }
+
+    #[test]
+    fn short_deck_wheel_is_a_six_to_nine_straight() {
+        let short_wheel = [(14, 0), (6, 1), (7, 2), (8, 3), (9, 0), (13, 1), (11, 2)];
+        let ten_high = [(10, 0), (6, 1), (7, 2), (8, 3), (9, 0), (13, 1), (11, 2)];
+        assert_eq!(hand_category(score(short_wheel)), HIGH_CARD);
+        assert_eq!(score_short(short_wheel), pack(STRAIGHT, &[card_rank(card(9, 0))]));
+        assert!(score_short(short_wheel) < score_short(ten_high));
+    }
+
+    #[test]
+    fn short_deck_has_no_deuces_to_fives() {
+        assert!(card_in_deck(card(2, 0), DeckType::Standard));
+        assert!(!card_in_deck(card(2, 0), DeckType::ShortDeck));
+        assert!(!card_in_deck(card(5, 3), DeckType::ShortDeck));
+        assert!(card_in_deck(card(6, 3), DeckType::ShortDeck));
+        assert!(!card_in_deck(DECK_SIZE, DeckType::Standard));
+    }
+}
@@ -1,7 +1,11 @@
+use crate::constants::{ACCEPTED_INPUT_TYPES, TOTAL_CARDS_NEEDED};
 use crate::error::PokerError;
-use crate::state::{GameStage, PokerGame, PokerTable};
+use crate::events::{DeckCommitted, StageAdvanced};
+use crate::state::{GameStage, OffsetProof, PokerGame, PokerTable};
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::clock::Clock;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::sysvar::slot_hashes;
 use inco_lightning::cpi::accounts::Operation;
 use inco_lightning::cpi::{self, e_add, new_euint128};
 use inco_lightning::program::IncoLightning;
@@ -10,13 +14,31 @@ use inco_lightning::types::Euint128;
 /// Process cards in mini-batches (2 cards per batch, 8 batches total)
 ///
 /// NEW FLOW:
-/// - Batch 0: Uses blockhash for shuffle seed and offset (no e_rand)
+/// - Batch 0: Uses the most recent SlotHashes entry for shuffle seed and offset (no e_rand)
 /// - Batch 1-6: Process cards 2-13
-/// - Batch 7: Process card 14, set cards_processed = true, stage = Playing
+/// - Batch 7: Process card 14
 ///
-/// Uses blockhash instead of e_rand to avoid oscillation issues:
-/// - shuffle_seed = slot hash (for Fisher-Yates shuffle)
-/// - card_offset = encrypted(slot % 52) (for card value offset)
+/// Batch 0 must land first since every other batch uses its offset and
+/// deal map; batches 1-7 may then arrive in any order. Once all 15 cards are
+/// in (tracked per card in cards_submitted_mask), cards_processed = true,
+/// stage = Playing.
+///
+/// Re-submitting a batch that already landed is a no-op, so the backend can
+/// safely retry a transaction whose confirmation was lost.
+///
+/// Every processed batch is folded into `deck_commitment`, which is emitted
+/// in DeckCommitted once the deal completes so the submitted deck can be
+/// audited after the game account is closed.
+///
+/// Uses a slot hash instead of e_rand to avoid oscillation issues. The
+/// hash is mixed with the game key so it can't be predicted from the slot
+/// number alone:
+/// - shuffle_seed = first 8 bytes of hash(slot_hash, game) (for Fisher-Yates shuffle)
+/// - card_offset = encrypted(hash(slot_hash, game) % 52) (for card value offset)
+/// - deal_map = permutation of all 15 card positions, so both hole and
+///   community cards are drawn from the shuffled pool
+/// - offset_proof = the slot, slot hash and plaintext offset, so verify_offset
+///   can recompute the offset later
 ///
 /// After batch 7 completes, backend can proceed with off-chain gameplay.
 pub fn handler<'info>(
@@ -29,12 +51,30 @@ pub fn handler<'info>(
     let game = &mut ctx.accounts.game;
 
     // ===== VALIDATION =====
+    require!(batch_index < 8, PokerError::InvalidBatchIndex);
+    // A wrong type would only show up as garbage at decryption time
+    require!(
+        ACCEPTED_INPUT_TYPES.contains(&input_type),
+        PokerError::InvalidInputType
+    );
+
+    // Already landed (e.g. a retried transaction): nothing to do
+    let batch_mask = batch_card_mask(batch_index);
+    if game.cards_submitted_mask & batch_mask == batch_mask {
+        msg!("Batch {} already processed, skipping", batch_index);
+        return Ok(());
+    }
+
     require!(
         game.stage == GameStage::Waiting,
         PokerError::InvalidGameStage
     );
     require!(!game.cards_processed, PokerError::CardsAlreadyProcessed);
-    require!(batch_index < 8, PokerError::InvalidBatchIndex);
+    // Offset and deal map come from batch 0
+    require!(
+        batch_index == 0 || game.cards_submitted_mask & 1 != 0,
+        PokerError::InvalidBatchIndex
+    );
 
     let cpi_program = ctx.accounts.inco_lightning_program.to_account_info();
     let authority = ctx.accounts.backend.to_account_info();
@@ -43,17 +83,19 @@ pub fn handler<'info>(
         signer: authority.clone(),
     };
 
-    // ===== BATCH 0: Generate shuffle seed and offset from blockhash =====
+    // ===== BATCH 0: Generate shuffle seed and offset from slot hash =====
     if batch_index == 0 {
-        // Get current slot as source of randomness
-        let clock = Clock::get()?;
-        let slot = clock.slot;
+        // Most recent slot hash as source of randomness
+        let (slot, slot_hash) = read_latest_slot_hash(&ctx.accounts.slot_hashes)?;
+        let seed = shuffle_seed_hash(&slot_hash, &game.key());
 
-        // Use slot as shuffle seed (deterministic, based on when tx lands)
-        game.shuffle_seed = slot;
+        // Use the mixed hash as shuffle seed (deterministic, based on when tx lands)
+        let mut seed_bytes = [0u8; 8];
+        seed_bytes.copy_from_slice(&seed[..8]);
+        game.shuffle_seed = u64::from_le_bytes(seed_bytes);
 
-        // Compute offset: slot % 52 (value between 0-51)
-        let offset_value = (slot % 52) as u128;
+        let offset = offset_from_seed(&seed);
+        let offset_value = offset as u128;
 
         // Encrypt the offset value using as_euint128
         // This creates an encrypted handle for the offset
@@ -63,16 +105,28 @@ pub fn handler<'info>(
         )?;
 
         game.card_offset = encrypted_offset;
-        game.shuffled_indices = do_simple_shuffle(slot);
+        game.shuffled_indices = do_simple_shuffle(game.shuffle_seed);
+        game.deal_map = do_deal_shuffle(&seed);
+        game.offset_proof = OffsetProof {
+            slot,
+            slot_hash,
+            offset,
+        };
 
         msg!(
-            "Batch 0: slot={}, offset={}, shuffled_indices={:?}",
+            "Batch 0: slot={}, offset={}, shuffled_indices={:?}, deal_map={:?}",
             slot,
             offset_value,
-            game.shuffled_indices
+            game.shuffled_indices,
+            game.deal_map
         );
     }
 
+    game.deck_commitment = chain_deck_commitment(
+        &game.deck_commitment,
+        &deck_batch_hash(batch_index, &card_0, &card_1),
+    );
+
     // Use stored encrypted offset for all batches
     let card_offset = game.card_offset;
 
@@ -88,6 +142,9 @@ pub fn handler<'info>(
             continue;
         }
 
+        // An empty ciphertext can't produce a real handle
+        require!(!cards[i].is_empty(), PokerError::InvalidCardCount);
+
         // Convert ciphertext to Eu128
         let enc_bck_crd: Euint128 = new_euint128(
             CpiContext::new(cpi_program.clone(), op_accounts.clone()),
@@ -104,28 +161,41 @@ pub fn handler<'info>(
             0,
         )?;
 
-        // Store based on card type
-        if actual_idx < 10 {
+        // Store at the slot picked by the deal map; reveals read the slots
+        // directly, so hole and community reveals both follow the same permutation
+        let dealt_idx = game.deal_map[actual_idx] as usize;
+        if dealt_idx < 10 {
             // Hole cards: apply shuffle to player assignment
-            let pair_idx = actual_idx / 2;
+            let pair_idx = dealt_idx / 2;
             let shuffled_pair = game.shuffled_indices[pair_idx % 5] as usize;
-            let card_slot = shuffled_pair * 2 + (actual_idx % 2);
+            let card_slot = shuffled_pair * 2 + (dealt_idx % 2);
             game.deal_cards[card_slot] = enc_offset_crd;
-            msg!("Hole {} -> slot {}", actual_idx, card_slot);
+            msg!("Card {} -> hole slot {}", actual_idx, card_slot);
         } else {
-            // Community cards (10-14) - stored directly (no shuffle for community)
-            let comm_idx = actual_idx - 10;
+            let comm_idx = dealt_idx - 10;
             game.community_cards[comm_idx] = enc_offset_crd;
-            msg!("Community {} -> slot {}", actual_idx, comm_idx);
+            msg!("Card {} -> community slot {}", actual_idx, comm_idx);
         }
     }
 
-    // ===== FINALIZE AFTER BATCH 7 =====
-    if batch_index == 7 {
+    game.cards_submitted_mask |= batch_mask;
+
+    // ===== FINALIZE ONCE ALL 15 CARDS ARE IN =====
+    if game.cards_submitted_mask == ALL_CARDS_MASK {
         game.cards_processed = true;
         game.stage = GameStage::Playing;
         msg!("All cards processed! cards_processed=true, stage=Playing");
         msg!("Backend can now proceed with off-chain gameplay");
+
+        emit!(StageAdvanced {
+            game: game.key(),
+            from: GameStage::Waiting,
+            to: GameStage::Playing,
+        });
+        emit!(DeckCommitted {
+            game: game.key(),
+            commitment: game.deck_commitment,
+        });
     } else {
         msg!("Batch {} done", batch_index);
     }
@@ -133,13 +203,67 @@ pub fn handler<'info>(
     Ok(())
 }
 
-/// Simple shuffle using slot as seed
-/// Deterministic based on the slot when batch 0 transaction lands
-fn do_simple_shuffle(slot: u64) -> [u8; 5] {
+/// Hash of one submitted batch: keccak(batch_index || len(card_0) as u32 LE || card_0 || card_1)
+pub fn deck_batch_hash(batch_index: u8, card_0: &[u8], card_1: &[u8]) -> [u8; 32] {
+    keccak::hashv(&[
+        &[batch_index],
+        &(card_0.len() as u32).to_le_bytes(),
+        card_0,
+        card_1,
+    ])
+    .to_bytes()
+}
+
+/// Fold a batch hash into the deck commitment: keccak(commitment || batch_hash)
+pub fn chain_deck_commitment(commitment: &[u8; 32], batch_hash: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[commitment, batch_hash]).to_bytes()
+}
+
+/// Shuffle seed for a game: hash(slot_hash, game), so it can't be predicted from the slot alone
+pub fn shuffle_seed_hash(slot_hash: &[u8; 32], game: &Pubkey) -> [u8; 32] {
+    hashv(&[slot_hash.as_ref(), game.as_ref()]).to_bytes()
+}
+
+/// Card offset for a seed: all 32 bytes folded mod 52 (value between 0-51)
+pub fn offset_from_seed(seed: &[u8; 32]) -> u8 {
+    seed.iter().fold(0u32, |acc, &b| (acc * 256 + b as u32) % 52) as u8
+}
+
+/// cards_submitted_mask value once all 15 cards have been processed
+const ALL_CARDS_MASK: u16 = (1 << TOTAL_CARDS_NEEDED) - 1;
+
+/// Bits of cards_submitted_mask covered by a batch (batch 7 only holds card 14)
+fn batch_card_mask(batch_index: u8) -> u16 {
+    (0b11u16 << (batch_index * 2)) & ALL_CARDS_MASK
+}
+
+/// Read the most recent (slot, hash) entry from the SlotHashes sysvar
+///
+/// The sysvar is too large to deserialize, so the first entry is read
+/// straight from the account data: u64 entry count, then (u64 slot, [u8; 32] hash).
+fn read_latest_slot_hash(slot_hashes_info: &AccountInfo) -> Result<(u64, [u8; 32])> {
+    let data = slot_hashes_info.try_borrow_data()?;
+    require!(data.len() >= 48, PokerError::InvalidSlotHashes);
+
+    let mut len_bytes = [0u8; 8];
+    len_bytes.copy_from_slice(&data[0..8]);
+    require!(u64::from_le_bytes(len_bytes) > 0, PokerError::InvalidSlotHashes);
+
+    let mut slot_bytes = [0u8; 8];
+    slot_bytes.copy_from_slice(&data[8..16]);
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&data[16..48]);
+
+    Ok((u64::from_le_bytes(slot_bytes), hash))
+}
+
+/// Simple shuffle using the shuffle seed
+/// Deterministic based on the slot hash when batch 0 transaction lands
+fn do_simple_shuffle(shuffle_seed: u64) -> [u8; 5] {
     let mut indices: [u8; 5] = [0, 1, 2, 3, 4];
-    let seed_bytes = slot.to_le_bytes();
+    let seed_bytes = shuffle_seed.to_le_bytes();
 
-    // Fisher-Yates shuffle using slot bytes as source of randomness
+    // Fisher-Yates shuffle using seed bytes as source of randomness
     for i in (1..5).rev() {
         let j = (seed_bytes[i % 8] as usize) % (i + 1);
         indices.swap(i, j);
@@ -148,18 +272,30 @@ fn do_simple_shuffle(slot: u64) -> [u8; 5] {
     indices
 }
 
+/// Shuffle all 15 card positions using the seed bytes not used by do_simple_shuffle
+fn do_deal_shuffle(seed: &[u8; 32]) -> [u8; 15] {
+    let mut deal_map: [u8; 15] = core::array::from_fn(|i| i as u8);
+
+    for i in (1..15).rev() {
+        let j = (seed[8 + i] as usize) % (i + 1);
+        deal_map.swap(i, j);
+    }
+
+    deal_map
+}
+
 #[derive(Accounts)]
 #[instruction(batch_index: u8)]
 pub struct ProcessCardsBatch<'info> {
     #[account(
-        constraint = table.backend == backend.key() @ PokerError::NotBackend
+        constraint = table.backend == backend.key() @ PokerError::NotBackend,
+        constraint = !table.paused @ PokerError::TablePaused
     )]
     pub table: Account<'info, PokerTable>,
 
     #[account(
         mut,
-        constraint = game.table == table.key() @ PokerError::NoActiveGame,
-        constraint = game.stage == GameStage::Waiting @ PokerError::InvalidGameStage
+        constraint = game.table == table.key() @ PokerError::NoActiveGame
     )]
     pub game: Account<'info, PokerGame>,
 
@@ -169,7 +305,48 @@ pub struct ProcessCardsBatch<'info> {
     )]
     pub backend: Signer<'info>,
 
+    /// CHECK: SlotHashes sysvar, read manually in batch 0
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+
     pub inco_lightning_program: Program<'info, IncoLightning>,
 
     pub system_program: Program<'info, System>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_folds_the_whole_seed_mod_52() {
+        let mut seed = [0u8; 32];
+        seed[31] = 53;
+        assert_eq!(offset_from_seed(&seed), 1);
+        // 256 = 4 * 52 + 48, so a byte further up still counts
+        seed = [0u8; 32];
+        seed[30] = 1;
+        assert_eq!(offset_from_seed(&seed), 48);
+        seed[0] = 0xff;
+        assert_ne!(offset_from_seed(&seed), 48);
+    }
+
+    #[test]
+    fn offset_varies_across_slot_hashes() {
+        let game = Pubkey::new_unique();
+        let offsets: std::collections::BTreeSet<u8> = (0..=255u8)
+            .map(|b| offset_from_seed(&shuffle_seed_hash(&[b; 32], &game)))
+            .collect();
+        assert!(offsets.iter().all(|&offset| offset < 52));
+        assert!(offsets.len() > 26, "only {} distinct offsets", offsets.len());
+    }
+
+    #[test]
+    fn seed_depends_on_the_game() {
+        let slot_hash = [7u8; 32];
+        assert_ne!(
+            shuffle_seed_hash(&slot_hash, &Pubkey::new_unique()),
+            shuffle_seed_hash(&slot_hash, &Pubkey::new_unique())
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use crate::constants::MAX_PLAYERS;
+use crate::error::PokerError;
+use crate::poker_hand::{card_in_deck, evaluate_7card_for};
+use crate::state::{GameStage, PlayerSeat, PokerGame, PokerTable};
+use anchor_lang::prelude::*;
+
+/// Rank each showdown hand on-chain from the attested card values
+///
+/// Called by backend at showdown, once attest_cards has verified the
+/// community cards and the hole cards of every seat still in the hand
+/// against their Inco handles. Each seat's best 5-card hand is scored and
+/// stored in `PlayerSeat.hand_rank`, the seats holding the best rank are
+/// recorded in `winners_mask`, and settle_game then only pays out to
+/// exactly those seats.
+///
+/// Remaining accounts: player_seat accounts (writable) of the seats in the hand.
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, RankHands<'info>>) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+
+    require!(
+        game.stage == GameStage::Playing,
+        PokerError::InvalidGameStage
+    );
+    require!(game.cards_processed, PokerError::CardsNotProcessed);
+    require!(game.ranked_mask == 0, PokerError::HandsAlreadyRanked);
+
+    let remaining = &ctx.remaining_accounts;
+    require!(
+        !remaining.is_empty() && remaining.len() <= MAX_PLAYERS as usize,
+        PokerError::InvalidCardCount
+    );
+
+    let community_mask: u16 = 0b11111 << 10;
+    require!(
+        game.cards_attested_mask & community_mask == community_mask,
+        PokerError::CardsNotAttested
+    );
+
+    let deck_type = game.deck_type;
+    let mut community_cards = [0u8; 5];
+    community_cards.copy_from_slice(&game.card_values[10..15]);
+
+    // Every card must be in the game's deck and appear only once
+    let mut seen_cards: u64 = 0;
+    for &card in community_cards.iter() {
+        require!(card_in_deck(card, deck_type), PokerError::InvalidCard);
+        require!(seen_cards & (1u64 << card) == 0, PokerError::DuplicateCard);
+        seen_cards |= 1u64 << card;
+    }
+
+    let table_key = ctx.accounts.table.key();
+    let mut best_hand_rank: u64 = 0;
+    let mut ranked_mask: u8 = 0;
+    let mut hand_ranks = [0u64; MAX_PLAYERS as usize];
+
+    for seat_info in remaining.iter() {
+        let mut seat: Account<PlayerSeat> = Account::try_from(seat_info)?;
+
+        let (expected, _) = Pubkey::find_program_address(
+            &[b"player_seat", table_key.as_ref(), seat.player.as_ref()],
+            ctx.program_id,
+        );
+        require!(expected == *seat_info.key, PokerError::PlayerNotAtTable);
+        require!(seat.seat_index < MAX_PLAYERS, PokerError::InvalidSeatIndex);
+        require!(
+            ranked_mask & (1 << seat.seat_index) == 0,
+            PokerError::InvalidSeatIndex
+        );
+
+        let (slot1, slot2) = game
+            .hole_card_slots(seat.seat_index)
+            .ok_or(PokerError::InvalidSeatIndex)?;
+        require!(
+            game.cards_attested_mask & (1 << slot1) != 0
+                && game.cards_attested_mask & (1 << slot2) != 0,
+            PokerError::CardsNotAttested
+        );
+        let hole = [game.card_values[slot1], game.card_values[slot2]];
+
+        for &card in hole.iter() {
+            require!(card_in_deck(card, deck_type), PokerError::InvalidCard);
+            require!(seen_cards & (1u64 << card) == 0, PokerError::DuplicateCard);
+            seen_cards |= 1u64 << card;
+        }
+
+        let hand_rank = evaluate_7card_for(
+            [
+                hole[0],
+                hole[1],
+                community_cards[0],
+                community_cards[1],
+                community_cards[2],
+                community_cards[3],
+                community_cards[4],
+            ],
+            deck_type,
+        ) as u64;
+
+        seat.hand_rank = hand_rank;
+        hand_ranks[seat.seat_index as usize] = hand_rank;
+        ranked_mask |= 1 << seat.seat_index;
+        best_hand_rank = best_hand_rank.max(hand_rank);
+
+        msg!("Seat {} hand rank {:#x}", seat.seat_index, hand_rank);
+
+        seat.exit(&crate::ID)?;
+    }
+
+    // Ties chop the pot, so every seat with the best rank wins
+    let winners_mask = (0..MAX_PLAYERS)
+        .filter(|&seat| ranked_mask & (1 << seat) != 0)
+        .filter(|&seat| hand_ranks[seat as usize] == best_hand_rank)
+        .fold(0u8, |mask, seat| mask | (1 << seat));
+
+    game.ranked_mask = ranked_mask;
+    game.best_hand_rank = best_hand_rank;
+    game.winners_mask = winners_mask;
+
+    msg!(
+        "Ranked {} hands, best rank {:#x}, winners {:#07b}",
+        remaining.len(),
+        best_hand_rank,
+        winners_mask
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RankHands<'info> {
+    #[account(
+        constraint = table.backend == backend.key() @ PokerError::NotBackend,
+        constraint = !table.paused @ PokerError::TablePaused
+    )]
+    pub table: Account<'info, PokerTable>,
+
+    #[account(
+        mut,
+        constraint = game.table == table.key() @ PokerError::NoActiveGame,
+        constraint = game.stage == GameStage::Playing @ PokerError::InvalidGameStage
+    )]
+    pub game: Account<'info, PokerGame>,
+
+    #[account(
+        constraint = backend.key() == game.backend_account @ PokerError::NotBackend
+    )]
+    pub backend: Signer<'info>,
+}
@@ -1,10 +1,15 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
 use crate::error::PokerError;
+use crate::events::{StackChangeReason, StackChanged, StageAdvanced};
 use crate::state::{GameStage, PlayerSeat, PokerGame, PokerTable};
+use crate::vault;
 
 /// Backend refunds all players and clears table state
 ///
 /// Remaining accounts: pairs of [player_seat, player_wallet] for each player.
+/// On token tables pass triples of [player_seat, player_wallet, player_token_account];
+/// chips are refunded to the token account and seat rent to the wallet.
 pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, RefundAll<'info>>) -> Result<()> {
     let table = &mut ctx.accounts.table;
     let game = &mut ctx.accounts.game;
@@ -12,20 +17,20 @@ pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, RefundAll<'info>>) -> R
     require!(table.current_game == Some(game.key()), PokerError::NoActiveGame);
 
     let remaining = &ctx.remaining_accounts;
+    let stride = if table.token_mint.is_some() { 3 } else { 2 };
     require!(
-        !remaining.is_empty() && remaining.len() % 2 == 0,
+        !remaining.is_empty() && remaining.len().is_multiple_of(stride),
         PokerError::InvalidRefundAccounts
     );
 
     let table_key = table.key();
-    let seeds = &[b"vault", table_key.as_ref(), &[ctx.bumps.vault]];
-    let signer = &[&seeds[..]];
 
     let mut refunded: u8 = 0;
 
-    for i in (0..remaining.len()).step_by(2) {
+    for i in (0..remaining.len()).step_by(stride) {
         let seat_info = &remaining[i];
         let player_wallet = &remaining[i + 1];
+        let refund_account = &remaining[i + stride - 1];
 
         let mut seat: Account<PlayerSeat> = Account::try_from(seat_info)?;
 
@@ -36,32 +41,49 @@ pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, RefundAll<'info>>) -> R
         require!(expected == *seat_info.key, PokerError::PlayerNotAtTable);
         require!(player_wallet.key == &seat.player, PokerError::PlayerNotAtTable);
 
-        let amount = seat.chips;
-        if amount > 0 {
-            let cpi_accounts = anchor_lang::system_program::Transfer {
-                from: ctx.accounts.vault.to_account_info(),
-                to: player_wallet.clone(),
-            };
-            let cpi_program = ctx.accounts.system_program.to_account_info();
-            anchor_lang::system_program::transfer(
-                CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
-                amount,
-            )?;
-        }
+        vault::pay_out(
+            table_key,
+            table.token_mint,
+            &ctx.accounts.vault,
+            table.vault_bump,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.system_program,
+            refund_account,
+            &seat.player,
+            seat.chips,
+        )?;
+
+        emit!(StackChanged {
+            game: Some(game.key()),
+            seat: seat.seat_index,
+            player: seat.player,
+            chips_before: seat.chips,
+            chips_after: 0,
+            reason: StackChangeReason::Refund,
+        });
 
         seat.chips = 0;
+        table.seats_taken &= !(1u8 << seat.seat_index);
         seat.close(player_wallet.clone())?;
         refunded = refunded.saturating_add(1);
     }
 
     table.player_count = table.player_count.saturating_sub(refunded);
     table.current_game = None;
+    let previous_stage = game.stage;
     game.stage = GameStage::Finished;
     game.pot = 0;
     game.winner_seat = None;
 
     msg!("Refunded {} player seats", refunded);
 
+    emit!(StageAdvanced {
+        game: game.key(),
+        from: previous_stage,
+        to: GameStage::Finished,
+    });
+
     Ok(())
 }
 
@@ -81,15 +103,21 @@ pub struct RefundAll<'info> {
     #[account(
         mut,
         seeds = [b"vault", table.key().as_ref()],
-        bump
+        bump = table.vault_bump
     )]
     pub vault: AccountInfo<'info>,
 
+    /// Vault-owned token account for the table mint (token tables only)
+    #[account(mut)]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
     #[account(
         mut,
         constraint = backend.key() == game.backend_account @ PokerError::NotBackend
     )]
     pub backend: Signer<'info>,
 
+    pub token_program: Option<Program<'info, Token>>,
+
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
@@ -0,0 +1,56 @@
+use crate::error::PokerError;
+use crate::events::RevealRequested;
+use crate::state::{GameStage, PlayerSeat, PokerGame, PokerTable};
+use anchor_lang::prelude::*;
+
+/// Ask a seat to reveal its hole cards after the hand
+///
+/// Anyone can ask; nothing is opened until the seat's owner approves with
+/// approve_reveal. A seat holds one request per game: while it is pending,
+/// further requests are rejected so nobody can swap in their own key ahead
+/// of the owner's approval. The owner can clear an unwanted request with
+/// decline_reveal. Requests left over from an earlier game lapse.
+pub fn handler(ctx: Context<RequestReveal>) -> Result<()> {
+    let game = &ctx.accounts.game;
+    let seat = &mut ctx.accounts.player_seat;
+    let requester = ctx.accounts.requester.key();
+
+    require!(game.cards_processed, PokerError::CardsNotProcessed);
+    require!(
+        seat.reveal_requested_by.is_none() || seat.reveal_request_game != game.key(),
+        PokerError::RevealAlreadyRequested
+    );
+
+    seat.reveal_requested_by = Some(requester);
+    seat.reveal_request_game = game.key();
+
+    msg!("{} asked seat {} to reveal", requester, seat.seat_index);
+
+    emit!(RevealRequested {
+        game: game.key(),
+        seat_index: seat.seat_index,
+        requester,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RequestReveal<'info> {
+    pub table: Account<'info, PokerTable>,
+
+    #[account(
+        constraint = game.table == table.key() @ PokerError::NoActiveGame,
+        constraint = game.stage == GameStage::Playing @ PokerError::InvalidGameStage
+    )]
+    pub game: Account<'info, PokerGame>,
+
+    #[account(
+        mut,
+        seeds = [b"player_seat", table.key().as_ref(), player_seat.player.as_ref()],
+        bump = player_seat.bump
+    )]
+    pub player_seat: Account<'info, PlayerSeat>,
+
+    pub requester: Signer<'info>,
+}
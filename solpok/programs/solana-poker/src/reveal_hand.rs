@@ -1,4 +1,5 @@
 use crate::error::PokerError;
+use crate::events::CardsDealt;
 use crate::state::{PlayerSeat, PokerGame, PokerTable};
 use anchor_lang::prelude::*;
 use inco_lightning::cpi::accounts::Allow;
@@ -43,6 +44,11 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, RevealHand<'info>>) -> Res
 
     let handle_1 = game.deal_cards[card_1_idx];
     let handle_2 = game.deal_cards[card_2_idx];
+    // Never hand out an unset slot
+    require!(
+        handle_1.0 != 0 && handle_2.0 != 0,
+        PokerError::InvalidCardCount
+    );
 
     msg!(
         "Revealing hand for seat {} (pair idx {}): slots {}, {}",
@@ -87,6 +93,11 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, RevealHand<'info>>) -> Res
         );
         allow(cpi_ctx, handle_2.0, true, player.key())?;
         msg!("Allowed card 2 decrypt access");
+
+        emit!(CardsDealt {
+            game: game.key(),
+            seat: seat_index,
+        });
     }
 
     Ok(())
@@ -0,0 +1,105 @@
+use crate::error::PokerError;
+use crate::state::{GameStage, PlayerSeat, PokerGame, PokerTable, Street};
+use anchor_lang::prelude::*;
+use inco_lightning::cpi::accounts::Allow;
+use inco_lightning::cpi::allow;
+use inco_lightning::program::IncoLightning;
+
+/// Backend opens a street's community cards to the seated players
+///
+/// Grants each listed player decrypt access to exactly the cards dealt on
+/// `street` (3 for the flop, 1 for the turn, 1 for the river) and records
+/// them in `community_revealed`. Streets must be opened in order; an
+/// already-open street can be granted again to more players.
+///
+/// Remaining accounts: one group per player of
+/// [player_seat, player, allowance for each card on the street].
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RevealStreet<'info>>,
+    street: Street,
+) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+
+    require!(game.cards_processed, PokerError::CardsNotProcessed);
+    require!(
+        game.community_revealed & street.required_mask() == street.required_mask(),
+        PokerError::StreetOutOfOrder
+    );
+
+    let cards = street.card_range();
+    let group_len = 2 + cards.len();
+    let remaining = &ctx.remaining_accounts;
+    require!(
+        !remaining.is_empty() && remaining.len().is_multiple_of(group_len),
+        PokerError::MissingAllowanceAccounts
+    );
+
+    let table_key = ctx.accounts.table.key();
+    let cpi_program = ctx.accounts.inco_lightning_program.to_account_info();
+    let authority = ctx.accounts.backend.to_account_info();
+
+    for group in remaining.chunks(group_len) {
+        let seat_info = &group[0];
+        let player = &group[1];
+
+        let seat: Account<PlayerSeat> = Account::try_from(seat_info)?;
+        let (expected, _) = Pubkey::find_program_address(
+            &[b"player_seat", table_key.as_ref(), seat.player.as_ref()],
+            ctx.program_id,
+        );
+        require!(expected == *seat_info.key, PokerError::PlayerNotAtTable);
+        require!(player.key == &seat.player, PokerError::PlayerNotAtTable);
+
+        for (k, card_idx) in cards.clone().enumerate() {
+            let cpi_ctx = CpiContext::new(
+                cpi_program.clone(),
+                Allow {
+                    allowance_account: group[2 + k].clone(),
+                    signer: authority.clone(),
+                    allowed_address: player.clone(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                },
+            );
+            allow(cpi_ctx, game.community_cards[card_idx].0, true, seat.player)?;
+        }
+
+        msg!("Seat {} allowed {:?} cards", seat.seat_index, street);
+    }
+
+    game.community_revealed |= street.card_mask();
+
+    msg!(
+        "{:?} revealed to {} players, community_revealed={:#07b}",
+        street,
+        remaining.len() / group_len,
+        game.community_revealed
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RevealStreet<'info> {
+    #[account(
+        constraint = table.backend == backend.key() @ PokerError::NotBackend,
+        constraint = !table.paused @ PokerError::TablePaused
+    )]
+    pub table: Account<'info, PokerTable>,
+
+    #[account(
+        mut,
+        constraint = game.table == table.key() @ PokerError::NoActiveGame,
+        constraint = game.stage == GameStage::Playing @ PokerError::InvalidGameStage
+    )]
+    pub game: Account<'info, PokerGame>,
+
+    #[account(
+        mut,
+        constraint = backend.key() == game.backend_account @ PokerError::NotBackend
+    )]
+    pub backend: Signer<'info>,
+
+    pub inco_lightning_program: Program<'info, IncoLightning>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,40 @@
+use crate::error::PokerError;
+use crate::events::AdminTransferProposed;
+use crate::state::PokerTable;
+use anchor_lang::prelude::*;
+
+/// Admin proposes a new admin for the table
+///
+/// Control only moves once the proposed key signs accept_admin, so a
+/// mistyped key can't lock the table. Proposing again replaces the pending
+/// admin; proposing the current admin cancels the transfer.
+pub fn handler(ctx: Context<SetAdmin>, new_admin: Pubkey) -> Result<()> {
+    let table = &mut ctx.accounts.table;
+
+    table.pending_admin = if new_admin == table.creator {
+        None
+    } else {
+        Some(new_admin)
+    };
+
+    msg!("Table {} admin transfer proposed to {}", table.table_id, new_admin);
+
+    emit!(AdminTransferProposed {
+        table: table.key(),
+        admin: table.creator,
+        pending_admin: new_admin,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetAdmin<'info> {
+    #[account(
+        mut,
+        has_one = creator @ PokerError::NotAdmin
+    )]
+    pub table: Account<'info, PokerTable>,
+
+    pub creator: Signer<'info>,
+}
@@ -0,0 +1,36 @@
+use crate::error::PokerError;
+use crate::events::BackendRotated;
+use crate::state::PokerTable;
+use anchor_lang::prelude::*;
+
+/// Admin replaces the table's backend authority
+///
+/// Takes effect for the next game: a game already in progress keeps the
+/// backend_account it was started with.
+pub fn handler(ctx: Context<SetBackend>, new_backend: Pubkey) -> Result<()> {
+    let table = &mut ctx.accounts.table;
+    let old_backend = table.backend;
+
+    table.backend = new_backend;
+
+    msg!("Table {} backend rotated {} -> {}", table.table_id, old_backend, new_backend);
+
+    emit!(BackendRotated {
+        table: table.key(),
+        old_backend,
+        new_backend,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetBackend<'info> {
+    #[account(
+        mut,
+        has_one = creator @ PokerError::NotAdmin
+    )]
+    pub table: Account<'info, PokerTable>,
+
+    pub creator: Signer<'info>,
+}
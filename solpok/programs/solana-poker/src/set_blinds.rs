@@ -0,0 +1,41 @@
+use crate::create_table::resolve_blinds;
+use crate::error::PokerError;
+use crate::events::BlindsChanged;
+use crate::state::PokerTable;
+use anchor_lang::prelude::*;
+
+/// Admin changes the table's blinds between hands
+///
+/// Validated like create_table (big_blind = 0 uses the standard multiplier,
+/// and buy_in_min must still cover the new big blind). The next start_game
+/// must post the new amounts.
+pub fn handler(ctx: Context<SetBlinds>, small_blind: u64, big_blind: u64) -> Result<()> {
+    let table = &mut ctx.accounts.table;
+
+    require!(table.current_game.is_none(), PokerError::GameInProgress);
+
+    let big_blind = resolve_blinds(small_blind, big_blind, table.buy_in_min)?;
+    table.small_blind = small_blind;
+    table.big_blind = big_blind;
+
+    msg!("Table {} blinds set to {}/{}", table.table_id, small_blind, big_blind);
+
+    emit!(BlindsChanged {
+        table: table.key(),
+        small_blind,
+        big_blind,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetBlinds<'info> {
+    #[account(
+        mut,
+        has_one = creator @ PokerError::NotAdmin
+    )]
+    pub table: Account<'info, PokerTable>,
+
+    pub creator: Signer<'info>,
+}
@@ -1,21 +1,43 @@
+use crate::constants::MAX_PLAYERS;
 use crate::error::PokerError;
+use crate::events::{HandSettled, StageAdvanced};
 use crate::state::{GameStage, PlayerSeat, PokerGame, PokerTable};
+use crate::vault;
 use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
 
-/// Settle the game and pay out the winner
+/// Settle the game and pay out the winners
 ///
 /// Called by backend after off-chain gameplay completes.
-/// Accepts the final pot amount and winner seat index.
+/// Accepts the final pot amount and the winning seat indices. When more
+/// than one seat wins (a chopped pot), the pot is split evenly and any odd
+/// chips go to the winner closest to the left of the dealer. If rank_hands
+/// ran, the winners must be exactly the seats it recorded in winners_mask;
+/// it must have run when two or more seats declared at showdown or the pot
+/// is chopped, so only an uncontested pot is paid on the backend's word.
+///
+/// If the table charges rake it is taken off the pot first and sent to the
+/// rake vault (less the referrer's share, if the table has a rake_referrer),
+/// except on hands that ended before the flop was revealed ("no flop, no drop").
+/// On SOL tables a referrer share too small to leave the referrer's wallet
+/// rent-exempt stays in the rake vault rather than failing the settlement.
+///
+/// Remaining accounts: pairs of [player_seat, player_wallet] for each
+/// winner, in the same order as `winner_seat_indices`. On token tables the
+/// second account is the winner's token account for the table mint.
 ///
 /// Flow:
-/// 1. Validate game is in Playing stage and cards are processed
-/// 2. Transfer pot from vault to winner's wallet
-/// 3. Update game state to Finished
+/// 1. Validate game is in Playing stage, cards are processed and no winner is set
+/// 2. Check the vault holds at least `final_pot`, then mark the game Finished and empty the pot
+/// 3. Transfer each winner's share from vault to their wallet
 /// 4. Clear table's current_game reference
-pub fn handler(ctx: Context<SettleGame>, winner_seat_index: u8, final_pot: u64) -> Result<()> {
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SettleGame<'info>>,
+    winner_seat_indices: Vec<u8>,
+    final_pot: u64,
+) -> Result<()> {
     let table = &mut ctx.accounts.table;
     let game = &mut ctx.accounts.game;
-    let winner_seat = &mut ctx.accounts.winner_seat;
 
     // Validate game state
     require!(
@@ -23,56 +45,245 @@ pub fn handler(ctx: Context<SettleGame>, winner_seat_index: u8, final_pot: u64)
         PokerError::InvalidGameStage
     );
     require!(game.cards_processed, PokerError::CardsNotProcessed);
+    require!(game.winner_seat.is_none(), PokerError::AlreadySettled);
+    require!(
+        !winner_seat_indices.is_empty(),
+        PokerError::WinnerNotDetermined
+    );
+    require!(
+        winner_seat_indices.len() <= MAX_PLAYERS as usize,
+        PokerError::InvalidSeatIndex
+    );
+
+    let remaining = &ctx.remaining_accounts;
     require!(
-        winner_seat.seat_index == winner_seat_index,
+        remaining.len() == winner_seat_indices.len() * 2,
         PokerError::PlayerNotAtTable
     );
 
+    let mut seen_mask: u8 = 0;
+    for &seat_index in winner_seat_indices.iter() {
+        require!(seat_index < MAX_PLAYERS, PokerError::InvalidSeatIndex);
+        require!(
+            seen_mask & (1 << seat_index) == 0,
+            PokerError::InvalidSeatIndex
+        );
+        seen_mask |= 1 << seat_index;
+    }
+
+    // A contested showdown or a chop must be ranked on-chain first
+    if game.requires_ranking(winner_seat_indices.len()) {
+        require!(game.ranked_mask != 0, PokerError::HandsNotRanked);
+    }
+
+    // Once hands are ranked on-chain, settlement is purely a payout step
+    if game.ranked_mask != 0 {
+        require!(seen_mask == game.winners_mask, PokerError::NotBestHand);
+    }
+
+    // No flop, no drop: only pots that saw a flop are raked
+    let rake = table.hand_rake(final_pot, game.community_revealed);
+
     // Use final_pot from backend (includes all bets collected off-chain)
-    let payout_amount = final_pot;
-    let winner = winner_seat.player;
+    let winnings = final_pot
+        .checked_sub(rake)
+        .ok_or(PokerError::ArithmeticOverflow)?;
+    let winner_count = winner_seat_indices.len();
+    let split = PotSplit::new(winnings, &winner_seat_indices, game.dealer_seat)
+        .ok_or(PokerError::WinnerNotDetermined)?;
+
+    // The vault must cover the whole pot before anything is paid out
+    let vault_balance = vault::balance(
+        table.token_mint,
+        &ctx.accounts.vault,
+        &ctx.accounts.vault_token_account,
+    )?;
+    require!(vault_balance >= final_pot, PokerError::VaultUnderfunded);
+
+    // Settle the game state before any lamports move
+    game.winner_seat = Some(split.odd_chip_seat);
+    game.stage = GameStage::Finished;
+    game.pot = 0;
 
-    // Transfer pot to winner via vault PDA
     let table_key = table.key();
-    let seeds = &[b"vault", table_key.as_ref(), &[ctx.bumps.vault]];
-    let signer = &[&seeds[..]];
-
-    // Only transfer if there's something to pay
-    if payout_amount > 0 {
-        let cpi_accounts = anchor_lang::system_program::Transfer {
-            from: ctx.accounts.vault.to_account_info(),
-            to: ctx.accounts.winner_wallet.to_account_info(),
+
+    if rake > 0 {
+        let mut referrer_cut = table.referrer_cut(rake);
+
+        // create_table only allows a referrer share when a referrer is set
+        let referrer = table.rake_referrer.filter(|_| referrer_cut > 0);
+        let referrer_account = match referrer {
+            Some(_) => Some(
+                ctx.accounts
+                    .rake_referrer_account
+                    .as_ref()
+                    .ok_or(PokerError::MissingReferrerAccount)?
+                    .to_account_info(),
+            ),
+            None => None,
         };
-        let cpi_program = ctx.accounts.system_program.to_account_info();
 
-        anchor_lang::system_program::transfer(
-            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
-            payout_amount,
+        // A SOL cut that would leave the referrer's wallet below rent
+        // exemption cannot be transferred; keep it in the rake vault instead
+        if let Some(account) = referrer_account.as_ref() {
+            if table.token_mint.is_none() {
+                let rent_minimum = Rent::get()?.minimum_balance(account.data_len());
+                if !referrer_cut_lands(account.lamports(), referrer_cut, rent_minimum) {
+                    referrer_cut = 0;
+                }
+            }
+        }
+
+        let rake_recipient = match &ctx.accounts.rake_token_account {
+            Some(rake_token_account) => rake_token_account.to_account_info(),
+            None => ctx.accounts.rake_vault.to_account_info(),
+        };
+        vault::pay_out(
+            table_key,
+            table.token_mint,
+            &ctx.accounts.vault,
+            table.vault_bump,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.system_program,
+            &rake_recipient,
+            &ctx.accounts.rake_vault.key(),
+            rake - referrer_cut,
         )?;
+
+        if let (Some(referrer), Some(referrer_account)) = (referrer, referrer_account) {
+            if referrer_cut > 0 {
+                vault::pay_out(
+                    table_key,
+                    table.token_mint,
+                    &ctx.accounts.vault,
+                    table.vault_bump,
+                    &ctx.accounts.vault_token_account,
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.system_program,
+                    &referrer_account,
+                    &referrer,
+                    referrer_cut,
+                )?;
+            }
+        }
+        msg!(
+            "Rake of {} taken from pot of {} ({} to referrer)",
+            rake,
+            final_pot,
+            referrer_cut
+        );
     }
 
-    // Update game state
-    game.winner_seat = Some(winner_seat_index);
-    game.stage = GameStage::Finished;
-    game.pot = 0;
-    game.payouts[winner_seat_index as usize] = payout_amount;
+    for (i, &seat_index) in winner_seat_indices.iter().enumerate() {
+        let seat_info = &remaining[i * 2];
+        let winner_wallet = &remaining[i * 2 + 1];
+
+        let seat: Account<PlayerSeat> = Account::try_from(seat_info)?;
+
+        let (expected, _) = Pubkey::find_program_address(
+            &[b"player_seat", table_key.as_ref(), seat.player.as_ref()],
+            ctx.program_id,
+        );
+        require!(expected == *seat_info.key, PokerError::PlayerNotAtTable);
+        require!(seat.seat_index == seat_index, PokerError::PlayerNotAtTable);
+
+        let payout_amount = split.payout(seat_index);
+
+        vault::pay_out(
+            table_key,
+            table.token_mint,
+            &ctx.accounts.vault,
+            table.vault_bump,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.system_program,
+            winner_wallet,
+            &seat.player,
+            payout_amount,
+        )?;
+
+        game.payouts[seat_index as usize] = payout_amount;
+
+        msg!(
+            "Winner seat {} {} ({}) won {} lamports",
+            seat_index,
+            game.position_label(seat_index),
+            seat.player,
+            payout_amount
+        );
+    }
 
     // Clear table's current game
     table.current_game = None;
 
     msg!(
-        "Game {} finished. Winner seat {} ({}) won {} lamports",
+        "Game {} finished. Pot of {} lamports split between {} winner(s)",
         game.game_id,
-        winner_seat_index,
-        winner,
-        payout_amount
+        final_pot,
+        winner_count
     );
 
+    emit!(StageAdvanced {
+        game: game.key(),
+        from: GameStage::Playing,
+        to: GameStage::Finished,
+    });
+    emit!(HandSettled {
+        game: game.key(),
+        winners: winner_seat_indices,
+        pot: final_pot,
+        rake,
+    });
+
     Ok(())
 }
 
+/// Even split of a pot between its winners
+struct PotSplit {
+    share: u64,
+    odd_chips: u64,
+    /// Winner who also takes the odd chips: the first clockwise from the dealer's left
+    odd_chip_seat: u8,
+}
+
+impl PotSplit {
+    /// None when there are no winners to split between
+    fn new(winnings: u64, winner_seat_indices: &[u8], dealer_seat: u8) -> Option<Self> {
+        let odd_chip_seat = *winner_seat_indices
+            .iter()
+            .min_by_key(|&&seat| seats_left_of_dealer(seat, dealer_seat))?;
+        let winner_count = winner_seat_indices.len() as u64;
+        Some(Self {
+            share: winnings / winner_count,
+            odd_chips: winnings % winner_count,
+            odd_chip_seat,
+        })
+    }
+
+    /// Amount paid to one of the winning seats
+    fn payout(&self, seat_index: u8) -> u64 {
+        if seat_index == self.odd_chip_seat {
+            self.share + self.odd_chips
+        } else {
+            self.share
+        }
+    }
+}
+
+/// Whether a native-SOL referrer cut leaves the wallet rent-exempt
+/// (an unfunded wallet must receive at least the rent minimum)
+fn referrer_cut_lands(lamports: u64, cut: u64, rent_minimum: u64) -> bool {
+    lamports.saturating_add(cut) >= rent_minimum
+}
+
+/// Distance of a seat from the dealer's left, clockwise
+/// (the seat directly left of the dealer is 0, the dealer is last)
+fn seats_left_of_dealer(seat_index: u8, dealer_seat: u8) -> u8 {
+    (seat_index + MAX_PLAYERS - dealer_seat - 1) % MAX_PLAYERS
+}
+
 #[derive(Accounts)]
-#[instruction(winner_seat_index: u8, final_pot: u64)]
 pub struct SettleGame<'info> {
     #[account(
         mut
@@ -87,26 +298,34 @@ pub struct SettleGame<'info> {
     )]
     pub game: Account<'info, PokerGame>,
 
+    /// CHECK: Vault PDA to pay from
     #[account(
         mut,
-        constraint = winner_seat.seat_index == winner_seat_index @ PokerError::PlayerNotAtTable
+        seeds = [b"vault", table.key().as_ref()],
+        bump = table.vault_bump
     )]
-    pub winner_seat: Account<'info, PlayerSeat>,
+    pub vault: AccountInfo<'info>,
 
-    /// CHECK: Winner's wallet to receive payout
-    #[account(
-        mut,
-        constraint = winner_wallet.key() == winner_seat.player @ PokerError::PlayerNotAtTable
-    )]
-    pub winner_wallet: AccountInfo<'info>,
+    /// Vault-owned token account for the table mint (token tables only)
+    #[account(mut)]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
 
-    /// CHECK: Vault PDA to pay from
+    /// CHECK: Rake vault PDA receiving the table's rake
     #[account(
         mut,
-        seeds = [b"vault", table.key().as_ref()],
+        seeds = [b"rake_vault", table.key().as_ref()],
         bump
     )]
-    pub vault: AccountInfo<'info>,
+    pub rake_vault: AccountInfo<'info>,
+
+    /// Rake-vault-owned token account for the table mint (token tables only)
+    #[account(mut)]
+    pub rake_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Rake referrer's wallet (token account on token tables); checked
+    /// against table.rake_referrer in vault::pay_out
+    #[account(mut)]
+    pub rake_referrer_account: Option<UncheckedAccount<'info>>,
 
     #[account(
         mut,
@@ -114,5 +333,64 @@ pub struct SettleGame<'info> {
     )]
     pub backend: Signer<'info>,
 
+    pub token_program: Option<Program<'info, Token>>,
+
     pub system_program: Program<'info, System>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payouts(winnings: u64, winners: &[u8], dealer_seat: u8) -> Vec<u64> {
+        let split = PotSplit::new(winnings, winners, dealer_seat).unwrap();
+        winners.iter().map(|&seat| split.payout(seat)).collect()
+    }
+
+    #[test]
+    fn two_way_chop_gives_odd_chip_left_of_dealer() {
+        // Dealer on seat 1: seat 3 is closer to the dealer's left than seat 0
+        let paid = payouts(101, &[0, 3], 1);
+        assert_eq!(paid, vec![50, 51]);
+        assert_eq!(paid.iter().sum::<u64>(), 101);
+    }
+
+    #[test]
+    fn three_way_chop_gives_both_odd_chips_to_one_seat() {
+        // Dealer on seat 2: seat 4 is first to the left, then 0, then 2
+        let paid = payouts(302, &[0, 2, 4], 2);
+        assert_eq!(paid, vec![100, 100, 102]);
+        assert_eq!(paid.iter().sum::<u64>(), 302);
+    }
+
+    #[test]
+    fn dealer_takes_odd_chip_only_when_alone_left_of_it() {
+        // The dealer is last in order, so it loses the odd chip to any other winner
+        assert_eq!(payouts(7, &[1, 2], 2), vec![4, 3]);
+        assert_eq!(payouts(7, &[2], 2), vec![7]);
+    }
+
+    #[test]
+    fn even_pot_splits_without_odd_chips() {
+        let paid = payouts(300, &[4, 1, 3], 0);
+        assert_eq!(paid, vec![100, 100, 100]);
+    }
+
+    #[test]
+    fn no_winners_is_rejected() {
+        assert!(PotSplit::new(100, &[], 0).is_none());
+    }
+
+    #[test]
+    fn sub_rent_cut_to_unfunded_referrer_does_not_land() {
+        assert!(!referrer_cut_lands(0, 1_575, 890_880));
+        assert!(referrer_cut_lands(0, 890_880, 890_880));
+    }
+
+    #[test]
+    fn any_cut_lands_on_a_funded_referrer() {
+        assert!(referrer_cut_lands(890_880, 1, 890_880));
+        assert!(referrer_cut_lands(10_000_000, 1_575, 890_880));
+        assert!(referrer_cut_lands(u64::MAX, 1, 890_880));
+    }
+}
@@ -1,13 +1,13 @@
-use crate::constants::MIN_PLAYERS;
+use crate::constants::{MAX_PLAYERS, MIN_PLAYERS, WAITING_TIMEOUT_SECS};
 use crate::error::PokerError;
-use crate::state::{GameStage, PlayerSeat, PokerGame, PokerTable};
+use crate::events::{BlindsPosted, StackChangeReason, StackChanged};
+use crate::state::{DeckType, PlayerSeat, PokerGame, PokerTable, RevealChoice};
 use anchor_lang::prelude::*;
-use inco_lightning::types::Euint128;
 
 /// Start a new game at the table
 ///
 /// Flow:
-/// 1. Admin calls start_game with blind amounts
+/// 1. Admin calls start_game; blinds are the table's current small_blind/big_blind
 /// 2. Blind bets are collected from small blind and big blind players
 /// 3. Game is initialized in Waiting stage
 /// 4. Next: process_cards (8 batches) to shuffle and deal
@@ -17,92 +17,160 @@ pub fn handler<'info>(
     ctx: Context<'_, '_, 'info, 'info, StartGame<'info>>,
     game_id: u64,
     backend_account: Pubkey,
-    small_blind_amount: u64,
-    big_blind_amount: u64,
+    deck_type: u8,
 ) -> Result<()> {
-    let table = &mut ctx.accounts.table;
-    let game = &mut ctx.accounts.game;
+    let game_bump = ctx.bumps.game;
+    start(
+        &mut ctx.accounts.table,
+        &mut ctx.accounts.game,
+        &ctx.accounts.backend,
+        ctx.remaining_accounts,
+        game_bump,
+        game_id,
+        backend_account,
+        deck_type,
+    )
+}
 
+/// Shared by start_game (explicit id) and start_next_game (id = games_played)
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn start<'info>(
+    table: &mut Account<'info, PokerTable>,
+    game: &mut Account<'info, PokerGame>,
+    backend: &Signer<'info>,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    game_bump: u8,
+    game_id: u64,
+    backend_account: Pubkey,
+    deck_type: u8,
+) -> Result<()> {
     // Validate backend authority
-    require!(
-        ctx.accounts.backend.key() == table.backend,
-        PokerError::NotBackend
-    );
-    require!(
-        backend_account == ctx.accounts.backend.key(),
-        PokerError::NotBackend
-    );
+    require!(backend.key() == table.backend, PokerError::NotBackend);
+    require!(backend_account == backend.key(), PokerError::NotBackend);
     require!(table.current_game.is_none(), PokerError::GameInProgress);
     require!(
         table.player_count >= MIN_PLAYERS,
         PokerError::NotEnoughPlayers
     );
 
+    // Blinds always come from the table, so set_blinds applies to the next game
+    let small_blind_amount = table.small_blind;
+    let big_blind_amount = table.big_blind;
+
+    // Per-hand card and result state
+    game.reset_for_new_hand();
+
     // Initialize game state
     game.table = table.key();
     game.game_id = game_id;
-    game.stage = GameStage::Waiting;
     game.player_count = table.player_count;
+    game.waiting_deadline = Clock::get()?
+        .unix_timestamp
+        .saturating_add(WAITING_TIMEOUT_SECS);
+    game.deck_type = DeckType::try_from(deck_type)?;
 
-    // Initialize card state
-    game.shuffle_seed = 0;
-    game.card_offset = Euint128::default();
-    game.shuffled_indices = [0, 1, 2, 3, 4];
-    game.deal_cards = [Euint128::default(); 10];
-    game.community_cards = [Euint128::default(); 5];
-    game.cards_processed = false;
+    // Button on the lowest occupied seat; seat 0 may be empty
+    game.seats_mask = table.seats_taken;
+    game.dealer_seat = (0..MAX_PLAYERS)
+        .find(|&seat| table.seats_taken & (1 << seat) != 0)
+        .ok_or(PokerError::NotEnoughPlayers)?;
 
     // Backend account for off-chain gameplay management
     game.backend_account = backend_account;
-
-    // Result state
-    game.winner_seat = None;
-    game.payouts = [0; 5];
-    game.bump = ctx.bumps.game;
+    game.bump = game_bump;
 
     // Collect blind bets from players via remaining_accounts
     // Expected: [small_blind_seat, big_blind_seat]
     let mut initial_pot: u64 = 0;
 
-    if ctx.remaining_accounts.len() >= 2 {
-        // Small blind (seat index 0 relative to dealer, which is seat 0)
-        let small_blind_seat_info = &ctx.remaining_accounts[0];
-        let mut small_blind_seat: Account<PlayerSeat> = Account::try_from(small_blind_seat_info)?;
+    if remaining_accounts.len() >= 2 {
+        let small_blind_seat_info = &remaining_accounts[0];
+        let big_blind_seat_info = &remaining_accounts[1];
 
+        // The same seat passed twice would be charged both blinds
         require!(
-            small_blind_seat.chips >= small_blind_amount,
-            PokerError::InsufficientChips
+            small_blind_seat_info.key != big_blind_seat_info.key,
+            PokerError::InvalidSeatIndex
         );
-        small_blind_seat.chips -= small_blind_amount;
-        initial_pot += small_blind_amount;
+
+        let table_key = table.key();
+
+        // Small blind (first seat left of the dealer)
+        let mut small_blind_seat: Account<PlayerSeat> = Account::try_from(small_blind_seat_info)?;
+        require_seat_at_table(&small_blind_seat, small_blind_seat_info.key, &table_key)?;
+
+        (small_blind_seat.chips, initial_pot) =
+            take_blind(small_blind_seat.chips, initial_pot, small_blind_amount)?;
+        small_blind_seat.game = game.key();
+        small_blind_seat.reveal_choice = RevealChoice::Undecided;
+        small_blind_seat.reveal_requested_by = None;
+        small_blind_seat.reveal_request_game = Pubkey::default();
+        small_blind_seat.current_bet = small_blind_amount;
+        small_blind_seat.total_bet = small_blind_amount;
 
         small_blind_seat.exit(&crate::ID)?;
 
-        // Big blind (seat index 1 relative to dealer)
-        let big_blind_seat_info = &ctx.remaining_accounts[1];
+        emit!(StackChanged {
+            game: Some(game.key()),
+            seat: small_blind_seat.seat_index,
+            player: small_blind_seat.player,
+            chips_before: small_blind_seat.chips + small_blind_amount,
+            chips_after: small_blind_seat.chips,
+            reason: StackChangeReason::Blind,
+        });
+
+        // Big blind (second seat left of the dealer)
         let mut big_blind_seat: Account<PlayerSeat> = Account::try_from(big_blind_seat_info)?;
+        require_seat_at_table(&big_blind_seat, big_blind_seat_info.key, &table_key)?;
 
-        require!(
-            big_blind_seat.chips >= big_blind_amount,
-            PokerError::InsufficientChips
-        );
-        big_blind_seat.chips -= big_blind_amount;
-        initial_pot += big_blind_amount;
+        (big_blind_seat.chips, initial_pot) =
+            take_blind(big_blind_seat.chips, initial_pot, big_blind_amount)?;
+        big_blind_seat.game = game.key();
+        big_blind_seat.reveal_choice = RevealChoice::Undecided;
+        big_blind_seat.reveal_requested_by = None;
+        big_blind_seat.reveal_request_game = Pubkey::default();
+        big_blind_seat.current_bet = big_blind_amount;
+        big_blind_seat.total_bet = big_blind_amount;
 
         big_blind_seat.exit(&crate::ID)?;
 
+        emit!(StackChanged {
+            game: Some(game.key()),
+            seat: big_blind_seat.seat_index,
+            player: big_blind_seat.player,
+            chips_before: big_blind_seat.chips + big_blind_amount,
+            chips_after: big_blind_seat.chips,
+            reason: StackChangeReason::Blind,
+        });
+
         msg!(
-            "Blinds collected: small={} big={} total={}",
+            "Blinds collected: small={} (seat {} {}) big={} (seat {} {}) total={}",
             small_blind_amount,
+            small_blind_seat.seat_index,
+            game.position_label(small_blind_seat.seat_index),
             big_blind_amount,
+            big_blind_seat.seat_index,
+            game.position_label(big_blind_seat.seat_index),
             initial_pot
         );
+
+        emit!(BlindsPosted {
+            game: game.key(),
+            sb_seat: small_blind_seat.seat_index,
+            bb_seat: big_blind_seat.seat_index,
+            sb: small_blind_amount,
+            bb: big_blind_amount,
+        });
     }
 
     game.pot = initial_pot;
 
     // Link game to table
     table.current_game = Some(game.key());
+    table.games_played = table
+        .games_played
+        .checked_add(1)
+        .ok_or(PokerError::ArithmeticOverflow)?;
 
     msg!(
         "Game {} started at table {} with {} players, pot={}",
@@ -115,12 +183,34 @@ pub fn handler<'info>(
     Ok(())
 }
 
+/// Move a blind from a seat's stack into the pot: (chips after, pot after)
+fn take_blind(chips: u64, pot: u64, blind: u64) -> Result<(u64, u64)> {
+    let chips_after = chips
+        .checked_sub(blind)
+        .ok_or(PokerError::InsufficientChips)?;
+    let pot_after = pot
+        .checked_add(blind)
+        .ok_or(PokerError::ArithmeticOverflow)?;
+    Ok((chips_after, pot_after))
+}
+
+/// Check a seat account passed via remaining_accounts is this table's seat PDA
+fn require_seat_at_table(seat: &PlayerSeat, seat_key: &Pubkey, table_key: &Pubkey) -> Result<()> {
+    let (expected, _) = Pubkey::find_program_address(
+        &[b"player_seat", table_key.as_ref(), seat.player.as_ref()],
+        &crate::ID,
+    );
+    require!(expected == *seat_key, PokerError::PlayerNotAtTable);
+    Ok(())
+}
+
 #[derive(Accounts)]
 #[instruction(game_id: u64)]
 pub struct StartGame<'info> {
     #[account(
         mut,
-        constraint = table.backend == backend.key() @ PokerError::NotBackend
+        constraint = table.backend == backend.key() @ PokerError::NotBackend,
+        constraint = !table.paused @ PokerError::TablePaused
     )]
     pub table: Account<'info, PokerTable>,
 
@@ -138,3 +228,34 @@ pub struct StartGame<'info> {
 
     pub system_program: Program<'info, System>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blind_moves_from_stack_to_pot() {
+        assert_eq!(take_blind(1_000, 100, 250).unwrap(), (750, 350));
+    }
+
+    #[test]
+    fn short_stack_cannot_post_the_blind() {
+        assert_eq!(
+            take_blind(249, 0, 250).unwrap_err(),
+            PokerError::InsufficientChips.into()
+        );
+    }
+
+    #[test]
+    fn pot_near_u64_max_overflows_gracefully() {
+        assert_eq!(
+            take_blind(u64::MAX, u64::MAX - 1, 2).unwrap_err(),
+            PokerError::ArithmeticOverflow.into()
+        );
+        assert_eq!(
+            take_blind(u64::MAX, 1, u64::MAX).unwrap_err(),
+            PokerError::ArithmeticOverflow.into()
+        );
+        assert_eq!(take_blind(u64::MAX, 0, u64::MAX).unwrap(), (0, u64::MAX));
+    }
+}
@@ -0,0 +1,51 @@
+use crate::error::PokerError;
+use crate::start_game;
+use crate::state::{PokerGame, PokerTable};
+use anchor_lang::prelude::*;
+
+/// Start the table's next game, using table.games_played as the game_id
+///
+/// Same checks and blind collection as start_game; the caller no longer has
+/// to pick a unique id, and game PDAs count up 0, 1, 2, ... per table.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, StartNextGame<'info>>,
+    backend_account: Pubkey,
+    deck_type: u8,
+) -> Result<()> {
+    let game_id = ctx.accounts.table.games_played;
+    let game_bump = ctx.bumps.game;
+    start_game::start(
+        &mut ctx.accounts.table,
+        &mut ctx.accounts.game,
+        &ctx.accounts.backend,
+        ctx.remaining_accounts,
+        game_bump,
+        game_id,
+        backend_account,
+        deck_type,
+    )
+}
+
+#[derive(Accounts)]
+pub struct StartNextGame<'info> {
+    #[account(
+        mut,
+        constraint = table.backend == backend.key() @ PokerError::NotBackend,
+        constraint = !table.paused @ PokerError::TablePaused
+    )]
+    pub table: Account<'info, PokerTable>,
+
+    #[account(
+        init,
+        payer = backend,
+        space = PokerGame::LEN,
+        seeds = [b"game", table.key().as_ref(), &table.games_played.to_le_bytes()],
+        bump
+    )]
+    pub game: Account<'info, PokerGame>,
+
+    #[account(mut)]
+    pub backend: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -8,6 +8,7 @@ pub use poker_game::PokerGame;
 pub use poker_table::PokerTable;
 pub use random_state::RandomState;
 
+use crate::error::PokerError;
 use anchor_lang::prelude::*;
 
 /// Simplified game stages for the new flow
@@ -22,6 +23,89 @@ pub enum GameStage {
     Finished,
 }
 
+/// Which deck and hand ranking a game plays with
+/// - Standard: 52 cards, usual hold'em rankings
+/// - ShortDeck: 36 cards (deuces through fives removed), flush beats full
+///   house and A-6-7-8-9 is the lowest straight
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum DeckType {
+    #[default]
+    Standard,
+    ShortDeck,
+}
+
+impl TryFrom<u8> for DeckType {
+    type Error = anchor_lang::error::Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(DeckType::Standard),
+            1 => Ok(DeckType::ShortDeck),
+            _ => err!(PokerError::InvalidDeckType),
+        }
+    }
+}
+
+/// Public inputs behind a game's card offset, recorded when batch 0 lands
+///
+/// Anyone can recompute the offset from `slot_hash` and the game key
+/// (see process_cards::offset_from_seed) and compare it with `offset`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct OffsetProof {
+    /// Slot whose hash seeded the shuffle
+    pub slot: u64,
+    /// SlotHashes entry for `slot`
+    pub slot_hash: [u8; 32],
+    /// Plaintext card offset (0-51) that was encrypted into card_offset
+    pub offset: u8,
+}
+
+impl OffsetProof {
+    pub const LEN: usize = 8 + 32 + 1;
+}
+
+/// Whether a seat shows or mucks its hand at showdown
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum RevealChoice {
+    #[default]
+    Undecided,
+    Show,
+    Muck,
+}
+
+/// Community card streets, opened in order
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Street {
+    Flop,
+    Turn,
+    River,
+}
+
+impl Street {
+    /// Indices into community_cards dealt on this street
+    pub fn card_range(&self) -> core::ops::Range<usize> {
+        match self {
+            Street::Flop => 0..3,
+            Street::Turn => 3..4,
+            Street::River => 4..5,
+        }
+    }
+
+    /// community_revealed bits for this street's cards
+    pub fn card_mask(&self) -> u8 {
+        self.card_range().fold(0, |mask, i| mask | (1 << i))
+    }
+
+    /// community_revealed bits that must be set before this street opens
+    pub fn required_mask(&self) -> u8 {
+        match self {
+            Street::Flop => 0,
+            Street::Turn => Street::Flop.card_mask(),
+            Street::River => Street::Flop.card_mask() | Street::Turn.card_mask(),
+        }
+    }
+}
+
 /// Round summary for final settlement
 /// Contains the final state of bets when game ends
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
@@ -36,3 +120,4 @@ pub struct RoundSummary {
     pub acted_mask: u8,
     pub action_on: u8,
 }
+
@@ -1,3 +1,4 @@
+use super::RevealChoice;
 use anchor_lang::prelude::*;
 use inco_lightning::types::Euint128;
 
@@ -29,6 +30,12 @@ pub struct PlayerSeat {
     pub has_acted: bool,
     /// Hand rank for showdown (0 = not submitted, higher = better)
     pub hand_rank: u64,
+    /// Show or muck declared at showdown for `game`
+    pub reveal_choice: RevealChoice,
+    /// Who asked to see this hand via request_reveal, pending the player's approval
+    pub reveal_requested_by: Option<Pubkey>,
+    /// Game the pending reveal request was made in; older requests have lapsed
+    pub reveal_request_game: Pubkey,
     /// Bump seed for PDA
     pub bump: u8,
 }
@@ -36,6 +43,7 @@ pub struct PlayerSeat {
 impl PlayerSeat {
     /// 8 (discriminator) + 32 (game) + 32 (player) + 1 (seat_index) + 8 (chips)
     /// + 16 (hole_card_1) + 16 (hole_card_2) + 8 (current_bet) + 8 (total_bet)
-    /// + 1 (is_folded) + 1 (is_all_in) + 1 (has_acted) + 8 (hand_rank) + 1 (bump)
-    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 + 16 + 16 + 8 + 8 + 1 + 1 + 1 + 8 + 1;
+    /// + 1 (is_folded) + 1 (is_all_in) + 1 (has_acted) + 8 (hand_rank)
+    /// + 1 (reveal_choice) + 33 (reveal_requested_by) + 32 (reveal_request_game) + 1 (bump)
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 + 16 + 16 + 8 + 8 + 1 + 1 + 1 + 8 + 1 + 33 + 32 + 1;
 }
@@ -1,4 +1,5 @@
-use super::GameStage;
+use super::{DeckType, GameStage, OffsetProof};
+use crate::constants::MAX_PLAYERS;
 use anchor_lang::prelude::*;
 use inco_lightning::types::Euint128;
 
@@ -18,6 +19,8 @@ pub struct PokerGame {
     pub pot: u64,
     /// Total number of players in game
     pub player_count: u8,
+    /// Unix time after which a game still in Waiting can be cancelled by anyone
+    pub waiting_deadline: i64,
 
     // ===== CARD STATE =====
     /// Shuffle seed derived from blockhash (used for Fisher-Yates shuffle)
@@ -30,8 +33,32 @@ pub struct PokerGame {
     pub deal_cards: [Euint128; 10],
     /// Community cards (encrypted) - 5 cards total
     pub community_cards: [Euint128; 5],
+    /// Where each submitted card lands: deal_map[i] is the slot for card i
+    /// (0-9 = deal_cards, 10-14 = community_cards). A permutation of 0..15.
+    pub deal_map: [u8; 15],
     /// Whether all 8 batches of cards have been processed
     pub cards_processed: bool,
+    /// Cards processed so far (bit i = submitted card i, 15 bits)
+    pub cards_submitted_mask: u16,
+    /// Running keccak commitment to the submitted ciphertexts, chained per
+    /// batch in the order batches landed (see process_cards::chain_deck_commitment)
+    pub deck_commitment: [u8; 32],
+    /// Slot hash and plaintext offset from batch 0, for verify_offset
+    pub offset_proof: OffsetProof,
+    /// Community cards opened to players so far (bit i = community card i);
+    /// written only by reveal_street
+    pub community_revealed: u8,
+    /// Plaintext card index (0-51) per slot, recorded by attest_cards
+    /// (0-9 = deal_cards, 10-14 = community_cards)
+    pub card_values: [u8; 15],
+    /// Slots whose card_values were checked against Inco's attestation (bit i = slot i)
+    pub cards_attested_mask: u16,
+    /// Deck and hand ranking used by rank_hands
+    pub deck_type: DeckType,
+    /// Dealer button: the lowest occupied seat when the game started
+    pub dealer_seat: u8,
+    /// Seats occupied when the game started (bit i = seat i)
+    pub seats_mask: u8,
 
     // ===== ACCESS CONTROL =====
     /// Backend account that can decrypt all community cards
@@ -40,6 +67,14 @@ pub struct PokerGame {
     // ===== GAME RESULT =====
     /// Winner seat index (set during settlement)
     pub winner_seat: Option<u8>,
+    /// Best hand rank submitted via rank_hands (0 = not ranked)
+    pub best_hand_rank: u64,
+    /// Seats whose hands were ranked this game (bit i = seat i)
+    pub ranked_mask: u8,
+    /// Seats holding best_hand_rank; settle_game must pay exactly these
+    pub winners_mask: u8,
+    /// Seats that declared show or muck at showdown (bit i = seat i)
+    pub showdown_mask: u8,
     /// Final pot distribution amounts per player
     pub payouts: [u64; 5],
     /// Bump seed for PDA
@@ -49,27 +84,196 @@ pub struct PokerGame {
 impl PokerGame {
     /// Calculate space needed for account
     /// 8 (discriminator) + 32 (table) + 8 (game_id) + 1 (stage)
-    /// + 8 (pot) + 1 (player_count)
+    /// + 8 (pot) + 1 (player_count) + 8 (waiting_deadline)
     /// + 8 (shuffle_seed) + 16 (card_offset) + 5 (shuffled_indices)
-    /// + 160 (deal_cards) + 80 (community_cards) + 1 (cards_processed)
+    /// + 160 (deal_cards) + 80 (community_cards) + 15 (deal_map) + 1 (cards_processed)
+    /// + 2 (cards_submitted_mask) + 32 (deck_commitment) + 41 (offset_proof)
+    /// + 1 (community_revealed) + 15 (card_values) + 2 (cards_attested_mask)
+    /// + 1 (deck_type) + 1 (dealer_seat) + 1 (seats_mask)
     /// + 32 (backend_account)
-    /// + 2 (winner_seat) + 40 (payouts) + 1 (bump)
-    pub const LEN: usize = 8 + 32 + 8 + 1 + 8 + 1 + 8 + 16 + 5 + 160 + 80 + 1 + 32 + 2 + 40 + 1;
+    /// + 2 (winner_seat) + 8 (best_hand_rank) + 1 (ranked_mask) + 1 (winners_mask)
+    /// + 1 (showdown_mask) + 40 (payouts) + 1 (bump)
+    pub const LEN: usize =
+        8 + 32 + 8 + 1 + 8 + 1 + 8 + 8 + 16 + 5 + 160 + 80 + 15 + 1 + 2 + 32 + 41 + 1 + 15 + 2 + 1 + 1 + 1 + 32
+        + 2 + 8 + 1 + 1 + 1 + 40 + 1;
+
+    /// Clear everything that belongs to a single hand: stage, pot, card
+    /// handles and shuffle, processing progress and results
+    ///
+    /// Identity and setup (table, game_id, player_count, deck_type, button,
+    /// backend_account, bump) are left for the caller to set.
+    pub fn reset_for_new_hand(&mut self) {
+        self.stage = GameStage::Waiting;
+        self.pot = 0;
+
+        // Card state
+        self.shuffle_seed = 0;
+        self.card_offset = Euint128::default();
+        self.shuffled_indices = [0, 1, 2, 3, 4];
+        self.deal_cards = [Euint128::default(); 10];
+        self.community_cards = [Euint128::default(); 5];
+        self.deal_map = core::array::from_fn(|i| i as u8);
+        self.cards_processed = false;
+        self.cards_submitted_mask = 0;
+        self.deck_commitment = [0; 32];
+        self.offset_proof = OffsetProof::default();
+        self.community_revealed = 0;
+        self.card_values = [0; 15];
+        self.cards_attested_mask = 0;
+
+        // Result state
+        self.winner_seat = None;
+        self.best_hand_rank = 0;
+        self.ranked_mask = 0;
+        self.winners_mask = 0;
+        self.showdown_mask = 0;
+        self.payouts = [0; 5];
+    }
+
+    /// Table position of a seat relative to the dealer button, for logs and UIs
+    ///
+    /// Occupied seats are counted clockwise from dealer_seat, skipping
+    /// empty ones. Seats not dealt into this game are labelled "?".
+    pub fn position_label(&self, seat_index: u8) -> &'static str {
+        match seat_offset(self.seats_mask, self.dealer_seat, seat_index) {
+            Some(offset) => position_name(self.player_count.max(1), offset),
+            None => "?",
+        }
+    }
+
+    /// Whether settle_game may only pay the seats rank_hands picked
+    pub fn requires_ranking(&self, winner_count: usize) -> bool {
+        showdown_needs_ranking(self.showdown_mask, winner_count)
+    }
 
     /// Get hole cards for a specific player (using shuffled assignment)
     pub fn get_player_hole_cards(&self, player_idx: u8) -> Option<(Euint128, Euint128)> {
+        let (card1_idx, card2_idx) = self.hole_card_slots(player_idx)?;
+        Some((self.deal_cards[card1_idx], self.deal_cards[card2_idx]))
+    }
+
+    /// Slots (indices into deal_cards) holding a seat's two hole cards
+    pub fn hole_card_slots(&self, player_idx: u8) -> Option<(usize, usize)> {
         if player_idx >= 5 {
             return None;
         }
 
         // Find which position in deal_cards corresponds to this player
-        for (pos, &shuffled_player) in self.shuffled_indices.iter().enumerate() {
-            if shuffled_player == player_idx {
-                let card1_idx = pos * 2;
-                let card2_idx = pos * 2 + 1;
-                return Some((self.deal_cards[card1_idx], self.deal_cards[card2_idx]));
-            }
+        let pos = self
+            .shuffled_indices
+            .iter()
+            .position(|&shuffled_player| shuffled_player == player_idx)?;
+        Some((pos * 2, pos * 2 + 1))
+    }
+
+    /// Handle stored at a card slot (0-9 = deal_cards, 10-14 = community_cards)
+    pub fn card_handle(&self, slot: usize) -> Option<Euint128> {
+        match slot {
+            0..=9 => Some(self.deal_cards[slot]),
+            10..=14 => Some(self.community_cards[slot - 10]),
+            _ => None,
         }
-        None
+    }
+}
+
+/// A pot contested at showdown, or chopped, is decided by rank_hands
+///
+/// Folds happen off-chain, so a seat counts as still in the hand once it
+/// declares show or muck. Only a hand won without a contested showdown
+/// (everyone else folded) may be settled unranked.
+pub fn showdown_needs_ranking(showdown_mask: u8, winner_count: usize) -> bool {
+    showdown_mask.count_ones() > 1 || winner_count > 1
+}
+
+/// Seats clockwise from the dealer to `seat_index`, counting only occupied
+/// seats in `seats_mask` (None if the seat is empty)
+pub fn seat_offset(seats_mask: u8, dealer_seat: u8, seat_index: u8) -> Option<u8> {
+    (0..MAX_PLAYERS)
+        .map(|step| (dealer_seat + step) % MAX_PLAYERS)
+        .filter(|&seat| seats_mask & (1 << seat) != 0)
+        .position(|seat| seat == seat_index)
+        .map(|offset| offset as u8)
+}
+
+/// Position name for the seat `offset` seats clockwise from the button at
+/// a table of `count` players. Heads-up the button posts the small blind;
+/// the late positions (LJ, HJ, CO) are counted back from the button.
+pub fn position_name(count: u8, offset: u8) -> &'static str {
+    match (count, offset) {
+        (2, 0) => "BTN/SB",
+        (2, _) => "BB",
+        (_, 0) => "BTN",
+        (_, 1) => "SB",
+        (_, 2) => "BB",
+        (_, 3) => "UTG",
+        _ if offset + 1 == count => "CO",
+        _ if offset + 2 == count => "HJ",
+        _ if offset + 3 == count => "LJ",
+        (_, 4) => "UTG+1",
+        (_, 5) => "UTG+2",
+        _ => "MP",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(count: u8) -> Vec<&'static str> {
+        (0..count).map(|offset| position_name(count, offset)).collect()
+    }
+
+    #[test]
+    fn heads_up_button_is_the_small_blind() {
+        assert_eq!(names(2), ["BTN/SB", "BB"]);
+    }
+
+    #[test]
+    fn six_handed_positions() {
+        assert_eq!(names(6), ["BTN", "SB", "BB", "UTG", "HJ", "CO"]);
+    }
+
+    #[test]
+    fn nine_handed_positions() {
+        assert_eq!(
+            names(9),
+            ["BTN", "SB", "BB", "UTG", "UTG+1", "UTG+2", "LJ", "HJ", "CO"]
+        );
+    }
+
+    #[test]
+    fn offsets_skip_empty_seats_from_the_button() {
+        // Seats 2 and 4 occupied, button on 2
+        assert_eq!(seat_offset(0b10100, 2, 2), Some(0));
+        assert_eq!(seat_offset(0b10100, 2, 4), Some(1));
+        // Button on 4 wraps past seat 0
+        assert_eq!(seat_offset(0b10101, 4, 0), Some(1));
+        assert_eq!(seat_offset(0b10101, 4, 2), Some(2));
+    }
+
+    #[test]
+    fn unseated_seats_have_no_offset() {
+        assert_eq!(seat_offset(0b10100, 2, 0), None);
+        assert_eq!(seat_offset(0b10100, 2, 3), None);
+        assert_eq!(seat_offset(0b10100, 2, 7), None);
+    }
+
+    #[test]
+    fn contested_showdown_needs_ranking() {
+        assert!(showdown_needs_ranking(0b00011, 1));
+        assert!(showdown_needs_ranking(0b10100, 1));
+    }
+
+    #[test]
+    fn chopped_pot_needs_ranking() {
+        assert!(showdown_needs_ranking(0, 2));
+        assert!(showdown_needs_ranking(0b00001, 3));
+    }
+
+    #[test]
+    fn uncontested_pot_can_settle_unranked() {
+        // Everyone else folded; at most the winner declared
+        assert!(!showdown_needs_ranking(0, 1));
+        assert!(!showdown_needs_ranking(0b01000, 1));
     }
 }
@@ -1,10 +1,12 @@
+use crate::constants::BPS_DENOMINATOR;
+use crate::state::Street;
 use anchor_lang::prelude::*;
 
 /// Poker table configuration account
 /// Stores table settings and current game reference
 #[account]
 pub struct PokerTable {
-    /// Player who created the table (for UI display)
+    /// Table admin: the player who created the table, until transferred
     pub creator: Pubkey,
     /// Backend authority for game operations
     pub backend: Pubkey,
@@ -18,17 +20,125 @@ pub struct PokerTable {
     pub buy_in_max: u64,
     /// Small blind amount in lamports
     pub small_blind: u64,
+    /// Big blind amount in lamports
+    pub big_blind: u64,
+    /// SPL token mint for token tables (None = native SOL)
+    pub token_mint: Option<Pubkey>,
+    /// Rake taken from each pot in basis points (0 = no rake)
+    pub rake_bps: u16,
+    /// Maximum rake per pot (0 = uncapped)
+    pub rake_cap: u64,
+    /// Optional second rake beneficiary (e.g. the creator's referrer)
+    pub rake_referrer: Option<Pubkey>,
+    /// Referrer's share of each rake in basis points; the rake vault keeps the rest
+    pub referrer_share_bps: u16,
     /// Current active game (if any)
     pub current_game: Option<Pubkey>,
     /// Number of players currently at table
     pub player_count: u8,
+    /// Occupied seat indices (bit i = seat i)
+    pub seats_taken: u8,
     /// Bump seed for PDA derivation
     pub bump: u8,
+    /// Bump of the vault PDA [b"vault", table], used to sign payouts
+    pub vault_bump: u8,
+    /// Admin proposed via set_admin, waiting to accept
+    pub pending_admin: Option<Pubkey>,
+    /// Set by pause_table: new joins, games and dealing are refused until unpaused
+    pub paused: bool,
+    /// Games started at this table; start_next_game uses it as the next game_id
+    pub games_played: u64,
 }
 
 impl PokerTable {
     /// Account discriminator (8) + creator (32) + backend (32) + table_id (8) + max_players (1) 
-    /// + buy_in_min (8) + buy_in_max (8) + small_blind (8) + current_game (1 + 32) 
-    /// + player_count (1) + bump (1)
-    pub const LEN: usize = 8 + 32 + 32 + 8 + 1 + 8 + 8 + 8 + 33 + 1 + 1;
+    /// + buy_in_min (8) + buy_in_max (8) + small_blind (8) + big_blind (8) + token_mint (1 + 32)
+    /// + rake_bps (2) + rake_cap (8) + rake_referrer (1 + 32) + referrer_share_bps (2)
+    /// + current_game (1 + 32) + player_count (1) + seats_taken (1) + bump (1)
+    /// + vault_bump (1) + pending_admin (1 + 32) + paused (1) + games_played (8)
+    pub const LEN: usize =
+        8 + 32 + 32 + 8 + 1 + 8 + 8 + 8 + 8 + 33 + 2 + 8 + 33 + 2 + 33 + 1 + 1 + 1 + 1 + 33 + 1 + 8;
+
+    /// Rake owed on a pot: rake_bps of the pot, capped at rake_cap when set
+    pub fn rake_for(&self, pot: u64) -> u64 {
+        let rake = (pot as u128 * self.rake_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        if self.rake_cap > 0 {
+            rake.min(self.rake_cap)
+        } else {
+            rake
+        }
+    }
+
+    /// Rake owed on a finished hand: no flop, no drop
+    ///
+    /// Only reveal_street writes community_revealed, so a hand is raked once
+    /// its flop was opened to the players, not when the backend read the board.
+    pub fn hand_rake(&self, pot: u64, community_revealed: u8) -> u64 {
+        if community_revealed & Street::Flop.card_mask() == 0 {
+            0
+        } else {
+            self.rake_for(pot)
+        }
+    }
+
+    /// Referrer's cut of a rake; the rest stays with the rake vault
+    pub fn referrer_cut(&self, rake: u64) -> u64 {
+        (rake as u128 * self.referrer_share_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(rake_bps: u16, rake_cap: u64) -> PokerTable {
+        PokerTable {
+            creator: Pubkey::default(),
+            backend: Pubkey::default(),
+            table_id: 0,
+            max_players: 5,
+            buy_in_min: 0,
+            buy_in_max: 0,
+            small_blind: 0,
+            big_blind: 0,
+            token_mint: None,
+            rake_bps,
+            rake_cap,
+            rake_referrer: None,
+            referrer_share_bps: 0,
+            current_game: None,
+            player_count: 0,
+            seats_taken: 0,
+            bump: 0,
+            vault_bump: 0,
+            pending_admin: None,
+            paused: false,
+            games_played: 0,
+        }
+    }
+
+    const FLOP: u8 = 0b00111;
+
+    #[test]
+    fn rake_is_a_share_of_the_pot() {
+        assert_eq!(table(500, 0).rake_for(1_000_000), 50_000);
+        assert_eq!(table(0, 0).rake_for(1_000_000), 0);
+    }
+
+    #[test]
+    fn rake_stops_at_the_cap() {
+        let capped = table(500, 30_000);
+        assert_eq!(capped.rake_for(1_000_000), 30_000);
+        // Below the cap the percentage applies as usual
+        assert_eq!(capped.rake_for(400_000), 20_000);
+    }
+
+    #[test]
+    fn no_flop_no_drop() {
+        let raked = table(500, 0);
+        assert_eq!(raked.hand_rake(1_000_000, 0), 0);
+        assert_eq!(raked.hand_rake(1_000_000, FLOP), 50_000);
+        assert_eq!(raked.hand_rake(1_000_000, 0b11111), 50_000);
+        assert_eq!(table(500, 30_000).hand_rake(1_000_000, FLOP), 30_000);
+    }
 }
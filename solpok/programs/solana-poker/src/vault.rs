@@ -0,0 +1,166 @@
+use crate::error::PokerError;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+/// Move a player's deposit into the table vault
+///
+/// Native tables transfer lamports to the vault PDA. Token tables
+/// (`token_mint` set) transfer SPL tokens from the player's token account
+/// into the vault-owned token account. Passing token accounts to a native
+/// table, or omitting them on a token table, is rejected.
+#[allow(clippy::too_many_arguments)]
+pub fn deposit<'info>(
+    token_mint: Option<Pubkey>,
+    vault: &AccountInfo<'info>,
+    player: &AccountInfo<'info>,
+    player_token_account: &Option<Account<'info, TokenAccount>>,
+    vault_token_account: &Option<Account<'info, TokenAccount>>,
+    token_program: &Option<Program<'info, Token>>,
+    system_program: &Program<'info, System>,
+    amount: u64,
+) -> Result<()> {
+    match token_mint {
+        None => {
+            require!(
+                player_token_account.is_none() && vault_token_account.is_none(),
+                PokerError::MixedFundingMode
+            );
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: player.clone(),
+                        to: vault.clone(),
+                    },
+                ),
+                amount,
+            )
+        }
+        Some(mint) => {
+            let (Some(player_token_account), Some(vault_token_account), Some(token_program)) =
+                (player_token_account, vault_token_account, token_program)
+            else {
+                return err!(PokerError::MixedFundingMode);
+            };
+            require!(
+                player_token_account.mint == mint && player_token_account.owner == player.key(),
+                PokerError::InvalidTokenAccount
+            );
+            require!(
+                vault_token_account.mint == mint && vault_token_account.owner == vault.key(),
+                PokerError::InvalidTokenAccount
+            );
+            token::transfer(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    token::Transfer {
+                        from: player_token_account.to_account_info(),
+                        to: vault_token_account.to_account_info(),
+                        authority: player.clone(),
+                    },
+                ),
+                amount,
+            )
+        }
+    }
+}
+
+/// Amount the table vault can pay out
+///
+/// Lamports held by the vault PDA on native tables, or the token balance of
+/// the vault-owned token account on token tables.
+pub fn balance<'info>(
+    token_mint: Option<Pubkey>,
+    vault: &AccountInfo<'info>,
+    vault_token_account: &Option<Account<'info, TokenAccount>>,
+) -> Result<u64> {
+    match token_mint {
+        None => Ok(vault.lamports()),
+        Some(mint) => {
+            let Some(vault_token_account) = vault_token_account else {
+                return err!(PokerError::MixedFundingMode);
+            };
+            require!(
+                vault_token_account.mint == mint && vault_token_account.owner == vault.key(),
+                PokerError::InvalidTokenAccount
+            );
+            Ok(vault_token_account.amount)
+        }
+    }
+}
+
+/// Pay `amount` out of the table vault, signed by the vault PDA
+///
+/// For native tables `recipient` is the player's wallet. For token tables
+/// it must be `recipient_owner`'s token account for the table mint.
+#[allow(clippy::too_many_arguments)]
+pub fn pay_out<'info>(
+    table_key: Pubkey,
+    token_mint: Option<Pubkey>,
+    vault: &AccountInfo<'info>,
+    vault_bump: u8,
+    vault_token_account: &Option<Account<'info, TokenAccount>>,
+    token_program: &Option<Program<'info, Token>>,
+    system_program: &Program<'info, System>,
+    recipient: &AccountInfo<'info>,
+    recipient_owner: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let seeds = &[b"vault", table_key.as_ref(), &[vault_bump]];
+    let signer = &[&seeds[..]];
+
+    match token_mint {
+        None => {
+            require!(vault_token_account.is_none(), PokerError::MixedFundingMode);
+            require!(recipient.key == recipient_owner, PokerError::PlayerNotAtTable);
+            if amount == 0 {
+                return Ok(());
+            }
+            anchor_lang::system_program::transfer(
+                CpiContext::new_with_signer(
+                    system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: vault.clone(),
+                        to: recipient.clone(),
+                    },
+                    signer,
+                ),
+                amount,
+            )
+        }
+        Some(mint) => {
+            let (Some(vault_token_account), Some(token_program)) =
+                (vault_token_account, token_program)
+            else {
+                return err!(PokerError::MixedFundingMode);
+            };
+            require!(
+                vault_token_account.mint == mint && vault_token_account.owner == vault.key(),
+                PokerError::InvalidTokenAccount
+            );
+            require!(recipient.owner == &token::ID, PokerError::InvalidTokenAccount);
+            let recipient_matches = {
+                let data = recipient.try_borrow_data()?;
+                let recipient_token_account = TokenAccount::try_deserialize(&mut &data[..])?;
+                recipient_token_account.mint == mint
+                    && recipient_token_account.owner == *recipient_owner
+            };
+            require!(recipient_matches, PokerError::InvalidTokenAccount);
+            if amount == 0 {
+                return Ok(());
+            }
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    token::Transfer {
+                        from: vault_token_account.to_account_info(),
+                        to: recipient.clone(),
+                        authority: vault.clone(),
+                    },
+                    signer,
+                ),
+                amount,
+            )
+        }
+    }
+}
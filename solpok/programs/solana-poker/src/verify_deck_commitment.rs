@@ -0,0 +1,39 @@
+use crate::error::PokerError;
+use crate::process_cards::chain_deck_commitment;
+use crate::state::{PokerGame, PokerTable};
+use anchor_lang::prelude::*;
+
+/// Check a replayed deck against the game's deck commitment (read-only)
+///
+/// `batch_hashes` are deck_batch_hash(batch_index, card_0, card_1) for every
+/// processed batch, in the order the batches landed. Auditors hash the
+/// ciphertexts from the process_cards_batch transactions themselves, so
+/// only 32 bytes per batch need to fit in this transaction.
+pub fn handler(ctx: Context<VerifyDeckCommitment>, batch_hashes: Vec<[u8; 32]>) -> Result<()> {
+    let game = &ctx.accounts.game;
+
+    let commitment = batch_hashes
+        .iter()
+        .fold([0u8; 32], |commitment, batch_hash| {
+            chain_deck_commitment(&commitment, batch_hash)
+        });
+
+    require!(
+        commitment == game.deck_commitment,
+        PokerError::DeckCommitmentMismatch
+    );
+
+    msg!("Deck commitment verified over {} batches", batch_hashes.len());
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct VerifyDeckCommitment<'info> {
+    pub table: Account<'info, PokerTable>,
+
+    #[account(
+        constraint = game.table == table.key() @ PokerError::NoActiveGame
+    )]
+    pub game: Account<'info, PokerGame>,
+}
@@ -0,0 +1,38 @@
+use crate::error::PokerError;
+use crate::process_cards::{offset_from_seed, shuffle_seed_hash};
+use crate::state::{PokerGame, PokerTable};
+use anchor_lang::prelude::*;
+
+/// Check a claimed card offset against the game's offset proof (read-only)
+///
+/// Recomputes the offset from the slot hash recorded when batch 0 landed
+/// and requires it to match both the recorded offset and `claimed_offset`.
+/// The slot hash itself can be checked against any RPC node's history for
+/// `offset_proof.slot`.
+pub fn handler(ctx: Context<VerifyOffset>, claimed_offset: u8) -> Result<()> {
+    let game = &ctx.accounts.game;
+    let proof = &game.offset_proof;
+
+    // Batch 0 records the proof; before that there is nothing to check
+    require!(game.cards_submitted_mask & 1 != 0, PokerError::CardsNotProcessed);
+
+    let offset = offset_from_seed(&shuffle_seed_hash(&proof.slot_hash, &game.key()));
+    require!(
+        offset == proof.offset && offset == claimed_offset,
+        PokerError::OffsetMismatch
+    );
+
+    msg!("Card offset {} verified against slot {}", offset, proof.slot);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct VerifyOffset<'info> {
+    pub table: Account<'info, PokerTable>,
+
+    #[account(
+        constraint = game.table == table.key() @ PokerError::NoActiveGame
+    )]
+    pub game: Account<'info, PokerGame>,
+}
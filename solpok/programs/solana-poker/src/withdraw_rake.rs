@@ -0,0 +1,111 @@
+use crate::error::PokerError;
+use crate::state::PokerTable;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+/// Table creator withdraws the rake collected so far
+///
+/// Native tables sweep everything above the rake vault's rent-exempt
+/// minimum to the creator. Token tables sweep the rake token account into
+/// the creator's token account.
+pub fn handler(ctx: Context<WithdrawRake>) -> Result<()> {
+    let table = &ctx.accounts.table;
+    let table_key = table.key();
+    let seeds = &[b"rake_vault", table_key.as_ref(), &[ctx.bumps.rake_vault]];
+    let signer = &[&seeds[..]];
+
+    let amount = match table.token_mint {
+        None => {
+            let rent_exempt = Rent::get()?.minimum_balance(0);
+            let amount = ctx
+                .accounts
+                .rake_vault
+                .lamports()
+                .saturating_sub(rent_exempt);
+            if amount > 0 {
+                anchor_lang::system_program::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: ctx.accounts.rake_vault.to_account_info(),
+                            to: ctx.accounts.creator.to_account_info(),
+                        },
+                        signer,
+                    ),
+                    amount,
+                )?;
+            }
+            amount
+        }
+        Some(mint) => {
+            let (Some(rake_token_account), Some(creator_token_account), Some(token_program)) = (
+                &ctx.accounts.rake_token_account,
+                &ctx.accounts.creator_token_account,
+                &ctx.accounts.token_program,
+            ) else {
+                return err!(PokerError::MixedFundingMode);
+            };
+            require!(
+                rake_token_account.mint == mint
+                    && rake_token_account.owner == ctx.accounts.rake_vault.key(),
+                PokerError::InvalidTokenAccount
+            );
+            require!(
+                creator_token_account.mint == mint
+                    && creator_token_account.owner == ctx.accounts.creator.key(),
+                PokerError::InvalidTokenAccount
+            );
+            let amount = rake_token_account.amount;
+            if amount > 0 {
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        token_program.to_account_info(),
+                        token::Transfer {
+                            from: rake_token_account.to_account_info(),
+                            to: creator_token_account.to_account_info(),
+                            authority: ctx.accounts.rake_vault.to_account_info(),
+                        },
+                        signer,
+                    ),
+                    amount,
+                )?;
+            }
+            amount
+        }
+    };
+
+    msg!("Withdrew {} in rake from table {}", amount, table.table_id);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawRake<'info> {
+    #[account(
+        has_one = creator @ PokerError::NotAdmin
+    )]
+    pub table: Account<'info, PokerTable>,
+
+    /// CHECK: Rake vault PDA holding the collected rake
+    #[account(
+        mut,
+        seeds = [b"rake_vault", table.key().as_ref()],
+        bump
+    )]
+    pub rake_vault: AccountInfo<'info>,
+
+    /// Rake-vault-owned token account for the table mint (token tables only)
+    #[account(mut)]
+    pub rake_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Creator's token account for the table mint (token tables only)
+    #[account(mut)]
+    pub creator_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::state::{PokerTable, PlayerSeat};
+use crate::error::PokerError;
+use crate::events::{StackChangeReason, StackChanged};
+use crate::vault;
+
+/// Seated player cashes out part of their stack between games and keeps the seat
+///
+/// The remaining stack may not drop below the table's buy_in_min.
+///
+/// settle_game pays winners straight to their wallets and never debits the
+/// losers' chips, so `chips` alone doesn't prove the stack is still there.
+/// The backend, which tracks stacks off-chain, co-signs every withdrawal.
+pub fn handler(ctx: Context<WithdrawWinnings>, amount: u64) -> Result<()> {
+    let table = &ctx.accounts.table;
+    let player_seat = &mut ctx.accounts.player_seat;
+
+    // Withdrawals only between games
+    require!(table.current_game.is_none(), PokerError::GameInProgress);
+    require!(amount > 0, PokerError::InvalidBetAmount);
+
+    let new_stack = player_seat
+        .chips
+        .checked_sub(amount)
+        .ok_or(PokerError::InsufficientChips)?;
+    require!(new_stack >= table.buy_in_min, PokerError::InsufficientChips);
+
+    let recipient = match (table.token_mint, &ctx.accounts.player_token_account) {
+        (None, _) => ctx.accounts.player.to_account_info(),
+        (Some(_), Some(player_token_account)) => player_token_account.to_account_info(),
+        (Some(_), None) => return err!(PokerError::MixedFundingMode),
+    };
+
+    vault::pay_out(
+        table.key(),
+        table.token_mint,
+        &ctx.accounts.vault,
+        table.vault_bump,
+        &ctx.accounts.vault_token_account,
+        &ctx.accounts.token_program,
+        &ctx.accounts.system_program,
+        &recipient,
+        &ctx.accounts.player.key(),
+        amount,
+    )?;
+
+    let chips_before = player_seat.chips;
+    player_seat.chips = new_stack;
+
+    msg!(
+        "Player {} at seat {} withdrew {} lamports, stack now {}",
+        ctx.accounts.player.key(),
+        player_seat.seat_index,
+        amount,
+        new_stack
+    );
+
+    emit!(StackChanged {
+        game: None,
+        seat: player_seat.seat_index,
+        player: player_seat.player,
+        chips_before,
+        chips_after: new_stack,
+        reason: StackChangeReason::Withdrawal,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawWinnings<'info> {
+    pub table: Account<'info, PokerTable>,
+
+    /// CHECK: Vault PDA to pay the withdrawal from
+    #[account(
+        mut,
+        seeds = [b"vault", table.key().as_ref()],
+        bump = table.vault_bump
+    )]
+    pub vault: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"player_seat", table.key().as_ref(), player.key().as_ref()],
+        bump = player_seat.bump
+    )]
+    pub player_seat: Account<'info, PlayerSeat>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// Backend vouching for the player's off-chain stack
+    #[account(constraint = backend.key() == table.backend @ PokerError::NotBackend)]
+    pub backend: Signer<'info>,
+
+    /// Player's token account for the table mint (token tables only)
+    #[account(mut)]
+    pub player_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Vault-owned token account for the table mint (token tables only)
+    #[account(mut)]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub system_program: Program<'info, System>,
+}
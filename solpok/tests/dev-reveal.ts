@@ -0,0 +1,194 @@
+/**
+ * Test for the dev_reveal_pool debugging instruction
+ *
+ * dev_reveal_pool only exists when the program is built with the
+ * `dev-reveal` feature (localnet debugging). Against a normal build this
+ * test just checks the instruction is absent from the IDL.
+ *
+ * Run with a dev build:
+ * anchor build -- --features dev-reveal
+ * ./node_modules/.bin/ts-mocha -p ./tsconfig.json -t 1000000 "tests/dev-reveal.ts"
+ */
+
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { SolanaPoker } from "../target/types/solana_poker";
+import { encryptValue } from "@inco/solana-sdk/encryption";
+import { decrypt } from "@inco/solana-sdk/attested-decrypt";
+import nacl from "tweetnacl";
+
+const INCO_LIGHTNING_ID = new anchor.web3.PublicKey(
+    "5sjEbPiqgZrYwR31ahR6Uk9wf5awoX61YGg7jExQSwaj"
+);
+
+describe("dev_reveal_pool: dev-only card dump", () => {
+    const provider = anchor.AnchorProvider.env();
+    anchor.setProvider(provider);
+
+    const program = anchor.workspace.SolanaPoker as Program<SolanaPoker>;
+    const connection = provider.connection;
+    const admin = provider.wallet;
+    const player2 = anchor.web3.Keypair.generate();
+
+    const tableId = new anchor.BN(Date.now()).add(new anchor.BN(7));
+    const gameId = tableId.add(new anchor.BN(1));
+    const smallBlind = new anchor.BN(100_000);
+    const bigBlind = new anchor.BN(200_000);
+    const buyIn = new anchor.BN(10_000_000);
+
+    const hasDevReveal = program.idl.instructions.some(
+        (ix: any) => ix.name === "dev_reveal_pool" || ix.name === "devRevealPool"
+    );
+
+    const sleep = (ms: number) => new Promise((r) => setTimeout(r, ms));
+
+    const handleToBigInt = (val: any): bigint => {
+        if (typeof val === "bigint") return val;
+        if (anchor.BN.isBN(val)) return BigInt(val.toString());
+        if (typeof val === "object" && val["0"] !== undefined) return handleToBigInt(val["0"]);
+        return BigInt(val.toString());
+    };
+
+    const handleToBytesLE = (handle: any) => {
+        let v = handleToBigInt(handle);
+        const buf = Buffer.alloc(16);
+        for (let i = 0; i < 16; i++) {
+            buf[i] = Number(v & 0xffn);
+            v >>= 8n;
+        }
+        return buf;
+    };
+
+    const signMessage = async (msg: Uint8Array) => {
+        if (admin.signMessage) return admin.signMessage(msg);
+        return nacl.sign.detached(msg, (admin as any).payer.secretKey);
+    };
+
+    const pda = (seeds: Buffer[]) =>
+        anchor.web3.PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+    it("is only present in dev-reveal builds", async () => {
+        if (!hasDevReveal) {
+            if ((program.methods as any).devRevealPool !== undefined) {
+                throw new Error("devRevealPool exposed without the dev-reveal feature");
+            }
+            console.log("✓ dev_reveal_pool compiled out (release build)");
+        }
+    });
+
+    it("opens every card to the admin", async function () {
+        if (!hasDevReveal) {
+            this.skip();
+        }
+
+        const tablePda = pda([
+            Buffer.from("table"),
+            admin.publicKey.toBuffer(),
+            tableId.toArrayLike(Buffer, "le", 8),
+        ]);
+        const vaultPda = pda([Buffer.from("vault"), tablePda.toBuffer()]);
+        const gamePda = pda([
+            Buffer.from("game"),
+            tablePda.toBuffer(),
+            gameId.toArrayLike(Buffer, "le", 8),
+        ]);
+        const adminSeatPda = pda([
+            Buffer.from("player_seat"),
+            tablePda.toBuffer(),
+            admin.publicKey.toBuffer(),
+        ]);
+        const player2SeatPda = pda([
+            Buffer.from("player_seat"),
+            tablePda.toBuffer(),
+            player2.publicKey.toBuffer(),
+        ]);
+
+        await program.methods
+            .createTable(tableId, 2, buyIn, buyIn, smallBlind, bigBlind, admin.publicKey, null, 0, new anchor.BN(0), null, 0)
+            .accounts({ table: tablePda, vault: vaultPda, creator: admin.publicKey })
+            .rpc({ commitment: "confirmed" });
+
+        await provider.sendAndConfirm(
+            new anchor.web3.Transaction().add(
+                anchor.web3.SystemProgram.transfer({
+                    fromPubkey: admin.publicKey,
+                    toPubkey: player2.publicKey,
+                    lamports: 50_000_000,
+                })
+            )
+        );
+
+        await program.methods
+            .joinTable(buyIn)
+            .accounts({ table: tablePda, vault: vaultPda, playerSeat: adminSeatPda, player: admin.publicKey })
+            .rpc({ commitment: "confirmed" });
+        await program.methods
+            .joinTable(buyIn)
+            .accounts({ table: tablePda, vault: vaultPda, playerSeat: player2SeatPda, player: player2.publicKey })
+            .signers([player2])
+            .rpc({ commitment: "confirmed" });
+
+        await program.methods
+            .startGame(gameId, admin.publicKey, 0)
+            .accounts({ table: tablePda, game: gamePda, backend: admin.publicKey })
+            .remainingAccounts([
+                { pubkey: adminSeatPda, isWritable: true, isSigner: false },
+                { pubkey: player2SeatPda, isWritable: true, isSigner: false },
+            ])
+            .rpc({ commitment: "confirmed" });
+
+        for (let batch = 0; batch < 8; batch++) {
+            const card0 = Buffer.from(await encryptValue(BigInt(batch * 2)), "hex");
+            const card1 = Buffer.from(await encryptValue(BigInt(batch * 2 + 1)), "hex");
+            await program.methods
+                .processCardsBatch(batch, card0, card1, 0)
+                .accounts({
+                    table: tablePda,
+                    game: gamePda,
+                    backend: admin.publicKey,
+                    slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+                    incoLightningProgram: INCO_LIGHTNING_ID,
+                })
+                .rpc({ commitment: "confirmed" });
+        }
+
+        const game = await program.account.pokerGame.fetch(gamePda);
+        const handles = [...game.dealCards, ...game.communityCards];
+        const allowances = handles.map((h: any) => ({
+            pubkey: anchor.web3.PublicKey.findProgramAddressSync(
+                [handleToBytesLE(h), admin.publicKey.toBuffer()],
+                INCO_LIGHTNING_ID
+            )[0],
+            isWritable: true,
+            isSigner: false,
+        }));
+
+        await (program.methods as any)
+            .devRevealPool()
+            .accounts({
+                table: tablePda,
+                game: gamePda,
+                admin: admin.publicKey,
+                backend: admin.publicKey,
+                incoLightningProgram: INCO_LIGHTNING_ID,
+            })
+            .remainingAccounts(allowances)
+            .preInstructions([
+                anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 }),
+            ])
+            .rpc({ commitment: "confirmed" });
+
+        await sleep(3000);
+        const result = await decrypt(
+            handles.map((h: any) => handleToBigInt(h).toString()),
+            { address: admin.publicKey, signMessage }
+        );
+        if (result.plaintexts.length !== 15) {
+            throw new Error(`expected 15 plaintexts, got ${result.plaintexts.length}`);
+        }
+        console.log(
+            "✓ All cards decrypted:",
+            result.plaintexts.map((p: string) => Number(BigInt(p) % 52n))
+        );
+    });
+});
@@ -4,6 +4,14 @@ import { SolanaPoker } from "../target/types/solana_poker";
 import { encryptValue } from "@inco/solana-sdk/encryption";
 import { decrypt } from "@inco/solana-sdk/attested-decrypt";
 import nacl from "tweetnacl";
+import { keccak_256 } from "@noble/hashes/sha3";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getAccount,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
 
 const INCO_LIGHTNING_ID = new anchor.web3.PublicKey(
   "5sjEbPiqgZrYwR31ahR6Uk9wf5awoX61YGg7jExQSwaj"
@@ -31,17 +39,28 @@ describe("solana-poker: Simplified Flow", () => {
   const buyInMin = new anchor.BN(1_000_000);
   const buyInMax = new anchor.BN(1_000_000_000);
   const smallBlind = new anchor.BN(100_000); // 0.0001 SOL
-  const bigBlind = new anchor.BN(200_000); // 0.0002 SOL
+  const bigBlind = new anchor.BN(250_000); // 0.00025 SOL (2.5x small blind)
   const playerBuyIn = new anchor.BN(50_000_000);
+  // 5% rake on the main table, 30% of it to the referrer and 70% to the rake vault
+  const rakeBps = 500;
+  const referrerShareBps = 3_000;
+  const referrer = anchor.web3.Keypair.generate();
 
   let tablePda: anchor.web3.PublicKey;
   let vaultPda: anchor.web3.PublicKey;
+  let rakeVaultPda: anchor.web3.PublicKey;
   let gamePda: anchor.web3.PublicKey;
   let adminSeatPda: anchor.web3.PublicKey;
   let player2SeatPda: anchor.web3.PublicKey;
   let player3SeatPda: anchor.web3.PublicKey;
   let player4SeatPda: anchor.web3.PublicKey;
   let player5SeatPda: anchor.web3.PublicKey;
+  // Seats paid by test 6, as ranked on-chain in test 5c
+  let settledWinners: Array<{
+    seatIndex: number;
+    seatPda: anchor.web3.PublicKey;
+    wallet: anchor.web3.Keypair | null;
+  }> = [];
 
 async function sendAndConfirm(fn: () => Promise<string>, desc: string) {
   const sig = await fn();
@@ -145,6 +164,66 @@ async function sendAndConfirm(fn: () => Promise<string>, desc: string) {
 
   const toCardIndex = (plaintext: string) => Number(BigInt(plaintext) % 52n);
 
+  // cancelGame refunds and unseats the blind posters; put them back at
+  // the same seats with the same stacks so the main table keeps its seating
+  const cancelAndReseat = async (
+    game: anchor.web3.PublicKey,
+    players: anchor.web3.Keypair[],
+    desc: string
+  ) => {
+    const seats = await Promise.all(
+      players.map(async (player) => {
+        const seatPda = anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("player_seat"), tablePda.toBuffer(), player.publicKey.toBuffer()],
+          program.programId
+        )[0];
+        return { player, seatPda, seat: await program.account.playerSeat.fetch(seatPda) };
+      })
+    );
+    const sig = await sendAndConfirm(
+      () =>
+        program.methods
+          .cancelGame()
+          .accounts({
+            table: tablePda,
+            game,
+            vault: vaultPda,
+            backend: admin.publicKey,
+            authority: admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts(
+            seats.flatMap(({ player, seatPda }) => [
+              { pubkey: seatPda, isWritable: true, isSigner: false },
+              { pubkey: player.publicKey, isWritable: true, isSigner: false },
+            ])
+          )
+          .rpc(),
+      desc
+    );
+    for (const { player, seatPda, seat } of seats) {
+      if (await program.account.playerSeat.fetchNullable(seatPda)) {
+        throw new Error(`${desc}: seat ${seat.seatIndex} still open after cancelGame`);
+      }
+      await sendAndConfirm(
+        () =>
+          program.methods
+            .joinTableSeat(seat.chips.add(seat.totalBet), seat.seatIndex)
+            .accounts({
+              table: tablePda,
+              vault: vaultPda,
+              playerSeat: seatPda,
+              player: player.publicKey,
+              systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([player])
+            .rpc(),
+        `rejoin seat ${seat.seatIndex}`
+      );
+    }
+    return sig;
+  };
+
   let decryptedHoleCards: string[] = [];
   let decryptedHoleCardsBySeat: Array<{
     label: string;
@@ -243,6 +322,11 @@ async function sendAndConfirm(fn: () => Promise<string>, desc: string) {
       program.programId
     );
 
+    [rakeVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("rake_vault"), tablePda.toBuffer()],
+      program.programId
+    );
+
     [gamePda] = anchor.web3.PublicKey.findProgramAddressSync(
       [
         Buffer.from("game"),
@@ -310,10 +394,24 @@ async function sendAndConfirm(fn: () => Promise<string>, desc: string) {
     await sendAndConfirm(
       () =>
         program.methods
-          .createTable(tableId, maxPlayers, buyInMin, buyInMax, smallBlind, admin.publicKey)
+          .createTable(
+            tableId,
+            maxPlayers,
+            buyInMin,
+            buyInMax,
+            smallBlind,
+            bigBlind,
+            admin.publicKey,
+            null,
+            rakeBps,
+            new anchor.BN(0),
+            referrer.publicKey,
+            referrerShareBps
+          )
           .accounts({
             table: tablePda,
             vault: vaultPda,
+            rakeVault: rakeVaultPda,
             creator: admin.publicKey,
             systemProgram: anchor.web3.SystemProgram.programId,
           })
@@ -322,6 +420,35 @@ async function sendAndConfirm(fn: () => Promise<string>, desc: string) {
     );
     console.log("Table created:", tablePda.toBase58());
 
+    const [, expectedVaultBump] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), tablePda.toBuffer()],
+      program.programId
+    );
+    const createdTable = await program.account.pokerTable.fetch(tablePda);
+    if (createdTable.vaultBump !== expectedVaultBump) {
+      throw new Error(`vault bump ${createdTable.vaultBump}, expected ${expectedVaultBump}`);
+    }
+
+    // A zero-chip seat can never be created
+    try {
+      await program.methods
+        .joinTable(new anchor.BN(0))
+        .accounts({
+          table: tablePda,
+          vault: vaultPda,
+          playerSeat: adminSeatPda,
+          player: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      throw new Error("joined with a zero buy-in");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "InvalidBuyIn") {
+        throw err;
+      }
+      console.log("Zero buy-in rejected with InvalidBuyIn");
+    }
+
     // Admin joins
     await sendAndConfirm(
       () =>
@@ -339,6 +466,38 @@ async function sendAndConfirm(fn: () => Promise<string>, desc: string) {
     );
     console.log("Admin joined");
 
+    // Joining again with the same wallet is a clean domain error
+    try {
+      await program.methods
+        .joinTable(playerBuyIn)
+        .accounts({
+          table: tablePda,
+          vault: vaultPda,
+          playerSeat: adminSeatPda,
+          player: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      throw new Error("admin joined the same seat twice");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "SeatTaken") {
+        throw err;
+      }
+      console.log("Second join rejected with SeatTaken");
+    }
+
+    // Fund the referrer's wallet so test 6 sees its cut; settle_game keeps a
+    // cut too small to make an unfunded wallet rent-exempt in the rake vault
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: admin.publicKey,
+          toPubkey: referrer.publicKey,
+          lamports: 10_000_000,
+        })
+      )
+    );
+
     // Fund and join other players
     const extraPlayers = [player2, player3, player4, player5];
     const extraLabels = ["player2", "player3", "player4", "player5"];
@@ -386,178 +545,1754 @@ async function sendAndConfirm(fn: () => Promise<string>, desc: string) {
   });
 
   // ========================================
-  // TEST 2: Start game with blind bets
+  // TEST 1a: Reject invalid table configs
   // ========================================
-  it("2. Start game with blind bets", async () => {
-    // Small blind = seat 1 (player2), Big blind = seat 2 (player3)
+  const expectCreateTableError = async (
+    label: string,
+    offset: number,
+    config: {
+      tableId?: anchor.BN;
+      maxPlayers?: number;
+      buyInMin?: anchor.BN;
+      buyInMax?: anchor.BN;
+      smallBlind?: anchor.BN;
+      bigBlind?: anchor.BN;
+    },
+    expectedCode: string
+  ) => {
+    const badTableId = config.tableId ?? tableId.add(new anchor.BN(1_000 + offset));
+    const [badTablePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("table"),
+        admin.publicKey.toBuffer(),
+        badTableId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [badVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), badTablePda.toBuffer()],
+      program.programId
+    );
+    const [badRakeVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("rake_vault"), badTablePda.toBuffer()],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .createTable(
+          badTableId,
+          config.maxPlayers ?? maxPlayers,
+          config.buyInMin ?? buyInMin,
+          config.buyInMax ?? buyInMax,
+          config.smallBlind ?? smallBlind,
+          config.bigBlind ?? bigBlind,
+          admin.publicKey,
+          null,
+          0,
+          new anchor.BN(0),
+          null,
+          0
+        )
+        .accounts({
+          table: badTablePda,
+          vault: badVaultPda,
+          rakeVault: badRakeVaultPda,
+          creator: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      throw new Error(`createTable with ${label} should have failed`);
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== expectedCode) {
+        throw err;
+      }
+      console.log(`${label} rejected with ${expectedCode}`);
+    }
+  };
+
+  it("1a. Reject invalid table configs", async () => {
+    await expectCreateTableError("9 seats", 0, { maxPlayers: 9 }, "TooManySeats");
+    await expectCreateTableError(
+      "buy-in max below min",
+      1,
+      { buyInMax: buyInMin.subn(1) },
+      "InvalidTableConfig"
+    );
+    await expectCreateTableError(
+      "min buy-in under two big blinds",
+      2,
+      { buyInMin: bigBlind.muln(2).subn(1) },
+      "InvalidTableConfig"
+    );
+    await expectCreateTableError(
+      "big blind below small blind",
+      3,
+      { bigBlind: smallBlind.subn(1) },
+      "InvalidTableConfig"
+    );
+    await expectCreateTableError(
+      "zero small blind",
+      4,
+      { smallBlind: new anchor.BN(0) },
+      "InvalidTableConfig"
+    );
+    // Reusing the id of the table created in test 1
+    await expectCreateTableError("reused table id", 5, { tableId }, "TableIdTaken");
+  });
+
+  // ========================================
+  // TEST 1b: Cancel a game stuck in Waiting
+  // ========================================
+  it("1b. Cancel a game before cards are processed", async () => {
+    const cancelledGameId = gameId.add(new anchor.BN(1_000));
+    const [cancelledGamePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("game"),
+        tablePda.toBuffer(),
+        cancelledGameId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const blindSeats = [
+      { pubkey: player2SeatPda, isWritable: true, isSigner: false },
+      { pubkey: player3SeatPda, isWritable: true, isSigner: false },
+    ];
+    const chipsBefore = await Promise.all(
+      [player2SeatPda, player3SeatPda].map(
+        async (pda) => (await program.account.playerSeat.fetch(pda)).chips
+      )
+    );
+
     await sendAndConfirm(
       () =>
         program.methods
-          .startGame(gameId, admin.publicKey, smallBlind, bigBlind)
+          .startGame(cancelledGameId, admin.publicKey, 0)
           .accounts({
             table: tablePda,
-            game: gamePda,
-            admin: admin.publicKey,
+            game: cancelledGamePda,
+            backend: admin.publicKey,
             systemProgram: anchor.web3.SystemProgram.programId,
           })
-          .remainingAccounts([
-            { pubkey: player2SeatPda, isWritable: true, isSigner: false },
-            { pubkey: player3SeatPda, isWritable: true, isSigner: false },
-          ])
+          .remainingAccounts(blindSeats)
           .rpc(),
-      "startGame"
+      "startGame (to cancel)"
     );
 
-    const game = await program.account.pokerGame.fetch(gamePda);
-    console.log("Game started:");
-    console.log("  Game ID:", game.gameId.toString());
-    console.log("  Stage:", game.stage);
-    console.log("  Initial pot (blinds):", game.pot.toString());
-    console.log("  Player count:", game.playerCount);
-  });
+    // Refunds go to the blind posters' wallets
+    const refundAccounts = [
+      { pubkey: player2SeatPda, isWritable: true, isSigner: false },
+      { pubkey: player2.publicKey, isWritable: true, isSigner: false },
+      { pubkey: player3SeatPda, isWritable: true, isSigner: false },
+      { pubkey: player3.publicKey, isWritable: true, isSigner: false },
+    ];
+    const cancel = (authority: anchor.web3.Keypair | null) => {
+      const builder = program.methods
+        .cancelGame()
+        .accounts({
+          table: tablePda,
+          game: cancelledGamePda,
+          vault: vaultPda,
+          backend: admin.publicKey,
+          authority: authority ? authority.publicKey : admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(refundAccounts);
+      return authority ? builder.signers([authority]).rpc() : builder.rpc();
+    };
+
+    // Before the waiting deadline only the admin may cancel
+    try {
+      await cancel(player2);
+      throw new Error("non-admin cancelled before the waiting deadline");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "NotAdmin") {
+        throw err;
+      }
+      console.log("Early cancel by a player rejected with NotAdmin");
+    }
 
-  // ========================================
-  // TEST 3: Process cards (8 batches)
-  // ========================================
-  it("3. Process cards (8 batches)", async () => {
-    console.log("Card values to encrypt:", CARD_VALUES);
+    const wallets = [player2, player3];
+    const seatIndices = await Promise.all(
+      [player2SeatPda, player3SeatPda].map(
+        async (pda) => (await program.account.playerSeat.fetch(pda)).seatIndex
+      )
+    );
+    const seatRents = await Promise.all(
+      [player2SeatPda, player3SeatPda].map((pda) => connection.getBalance(pda))
+    );
+    const walletsBefore = await Promise.all(
+      wallets.map((w) => connection.getBalance(w.publicKey))
+    );
+    const tableBefore = await program.account.pokerTable.fetch(tablePda);
 
-    const encryptedCards: Buffer[] = [];
-    for (let i = 0; i < 15; i++) {
-      const hex = await encryptValue(BigInt(CARD_VALUES[i]));
-      encryptedCards.push(Buffer.from(hex, "hex"));
-      if (i < 4) {
-        console.log(`  card[${i}] value=${CARD_VALUES[i]} encrypted`);
+    await sendAndConfirm(() => cancel(null), "cancelGame");
+
+    const table = await program.account.pokerTable.fetch(tablePda);
+    if (table.currentGame !== null) {
+      throw new Error("Table still references the cancelled game");
+    }
+    // Each blind poster got their whole stack, blind included, plus the seat rent
+    for (let i = 0; i < wallets.length; i++) {
+      const gain = (await connection.getBalance(wallets[i].publicKey)) - walletsBefore[i];
+      if (gain !== chipsBefore[i].toNumber() + seatRents[i]) {
+        throw new Error(`seat ${seatIndices[i]} refunded ${gain}, expected ${chipsBefore[i].toNumber() + seatRents[i]}`);
       }
     }
+    const closedSeats = await Promise.all(
+      [player2SeatPda, player3SeatPda].map((pda) => program.account.playerSeat.fetchNullable(pda))
+    );
+    if (
+      closedSeats.some((seat) => seat !== null) ||
+      table.playerCount !== tableBefore.playerCount - 2 ||
+      seatIndices.some((idx) => (table.seatsTaken & (1 << idx)) !== 0)
+    ) {
+      throw new Error("cancelGame left the blind posters seated");
+    }
+    console.log("Cancelled game closed, blind posters refunded and unseated");
 
-    for (let batch = 0; batch < 8; batch++) {
-      const idx0 = batch * 2;
-      const idx1 = batch * 2 + 1;
-      const card0 = encryptedCards[idx0] || encryptedCards[0];
-      const card1 =
-        idx1 < 15
-          ? encryptedCards[idx1]
-          : Buffer.from(await encryptValue(0n), "hex");
-
+    // Back to the same seats for the games that follow
+    for (let i = 0; i < wallets.length; i++) {
       await sendAndConfirm(
         () =>
           program.methods
-            .processCardsBatch(batch, card0, card1, 0)
+            .joinTableSeat(chipsBefore[i], seatIndices[i])
             .accounts({
               table: tablePda,
-              game: gamePda,
-              backend: admin.publicKey,
-              incoLightningProgram: INCO_LIGHTNING_ID,
+              vault: vaultPda,
+              playerSeat: [player2SeatPda, player3SeatPda][i],
+              player: wallets[i].publicKey,
               systemProgram: anchor.web3.SystemProgram.programId,
             })
+            .signers([wallets[i]])
             .rpc(),
-        `processCardsBatch(${batch})`
+        `rejoin seat ${seatIndices[i]}`
       );
-
-      if (batch === 0 || batch === 7) {
-        await logCardOffset(`after batch ${batch}`);
-      }
     }
-
-    const game = await program.account.pokerGame.fetch(gamePda);
-    console.log("\nCards processed:");
-    console.log("  cards_processed:", game.cardsProcessed);
-    console.log("  stage:", game.stage);
-    console.log("  shuffle_seed (slot):", game.shuffleSeed.toString());
-    console.log("  shuffled_indices:", game.shuffledIndices);
   });
 
   // ========================================
-  // TEST 4: Reveal hands (allow decryption)
+  // TEST 1c: Reserve a specific seat
   // ========================================
-  it("4. Reveal all hands (allow decryption)", async () => {
-    const game = await program.account.pokerGame.fetch(gamePda);
-    const players = [
-      {
-        label: "admin",
-        publicKey: admin.publicKey,
-        seatPda: adminSeatPda,
-        signMessage: getSignMessage(),
-      },
-      {
-        label: "player2",
-        publicKey: player2.publicKey,
-        seatPda: player2SeatPda,
-        signMessage: getKeypairSignMessage(player2),
-      },
-      {
-        label: "player3",
-        publicKey: player3.publicKey,
-        seatPda: player3SeatPda,
-        signMessage: getKeypairSignMessage(player3),
-      },
-      {
-        label: "player4",
-        publicKey: player4.publicKey,
-        seatPda: player4SeatPda,
-        signMessage: getKeypairSignMessage(player4),
-      },
-      {
-        label: "player5",
-        publicKey: player5.publicKey,
-        seatPda: player5SeatPda,
-        signMessage: getKeypairSignMessage(player5),
-      },
-    ];
-
-    decryptedHoleCardsBySeat = [];
+  it("1c. Join at a chosen seat index", async () => {
+    const seatTableId = tableId.add(new anchor.BN(2_000));
+    const [seatTablePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("table"),
+        admin.publicKey.toBuffer(),
+        seatTableId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [seatVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), seatTablePda.toBuffer()],
+      program.programId
+    );
+    const [seatRakeVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("rake_vault"), seatTablePda.toBuffer()],
+      program.programId
+    );
+    const seatPdaFor = (player: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("player_seat"), seatTablePda.toBuffer(), player.toBuffer()],
+        program.programId
+      )[0];
 
-    for (const player of players) {
-      const seat = await program.account.playerSeat.fetch(player.seatPda);
-      const seatIndex = seat.seatIndex as number;
-      const pairIndex = game.shuffledIndices.findIndex(
-        (s: number) => s === seatIndex
-      );
-      if (pairIndex === -1) {
-        console.log(
-          `${player.label}: seat ${seatIndex} not found in shuffled indices`
-        );
-        continue;
-      }
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .createTable(
+            seatTableId,
+            maxPlayers,
+            buyInMin,
+            buyInMax,
+            smallBlind,
+            bigBlind,
+            admin.publicKey,
+            null,
+            0,
+            new anchor.BN(0),
+            null,
+            0
+          )
+          .accounts({
+            table: seatTablePda,
+            vault: seatVaultPda,
+            rakeVault: seatRakeVaultPda,
+            creator: admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc(),
+      "createTable (seat reservation)"
+    );
 
-      const h1 = extractHandle(game.dealCards[pairIndex * 2]);
-      const h2 = extractHandle(game.dealCards[pairIndex * 2 + 1]);
+    const joinSeat = (player: anchor.web3.Keypair | null, seatIndex: number) => {
+      const playerKey = player ? player.publicKey : admin.publicKey;
+      const builder = program.methods
+        .joinTableSeat(buyInMin, seatIndex)
+        .accounts({
+          table: seatTablePda,
+          vault: seatVaultPda,
+          playerSeat: seatPdaFor(playerKey),
+          player: playerKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        });
+      return player ? builder.signers([player]).rpc() : builder.rpc();
+    };
 
-      const allowance1 = anchor.web3.PublicKey.findProgramAddressSync(
-        [handleToBytesLE(h1), player.publicKey.toBuffer()],
-        INCO_LIGHTNING_ID
-      )[0];
-      const allowance2 = anchor.web3.PublicKey.findProgramAddressSync(
-        [handleToBytesLE(h2), player.publicKey.toBuffer()],
-        INCO_LIGHTNING_ID
-      )[0];
+    await sendAndConfirm(() => joinSeat(null, 3), "admin joinTableSeat(3)");
+    const adminSeat = await program.account.playerSeat.fetch(seatPdaFor(admin.publicKey));
+    if (adminSeat.seatIndex !== 3) {
+      throw new Error(`admin seated at ${adminSeat.seatIndex}, expected 3`);
+    }
+    console.log("Admin reserved seat 3");
 
-      await sendAndConfirm(
-        () =>
-          program.methods
-            .revealHand()
-            .accounts({
-              table: tablePda,
-              game: gamePda,
-              playerSeat: player.seatPda,
-              player: player.publicKey,
-              backend: admin.publicKey,
-              incoLightningProgram: INCO_LIGHTNING_ID,
-              systemProgram: anchor.web3.SystemProgram.programId,
-            })
-            .remainingAccounts([
-              { pubkey: allowance1, isWritable: true, isSigner: false },
-              { pubkey: allowance2, isWritable: true, isSigner: false },
-            ])
-            .rpc(),
-        `revealHand(${player.label})`
-        
-      );
+    try {
+      await joinSeat(player2, 3);
+      throw new Error("player2 took an occupied seat");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "SeatTaken") {
+        throw err;
+      }
+      console.log("Occupied seat rejected with SeatTaken");
+    }
 
-      if (player.signMessage) {
-        const handles = [handleToDecimalString(h1), handleToDecimalString(h2)];
-        try {
+    // Plain join_table skips the reserved seat
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .joinTable(buyInMin)
+          .accounts({
+            table: seatTablePda,
+            vault: seatVaultPda,
+            playerSeat: seatPdaFor(player2.publicKey),
+            player: player2.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([player2])
+          .rpc(),
+      "player2 joinTable"
+    );
+    const player2Seat = await program.account.playerSeat.fetch(seatPdaFor(player2.publicKey));
+    const seatTable = await program.account.pokerTable.fetch(seatTablePda);
+    if (player2Seat.seatIndex !== 0 || seatTable.seatsTaken !== 0b1001) {
+      throw new Error(
+        `unexpected seating: player2 at ${player2Seat.seatIndex}, mask ${seatTable.seatsTaken}`
+      );
+    }
+    console.log("player2 took the lowest free seat (0)");
+
+    // A paused table refuses new joins until the admin unpauses it
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .pauseTable()
+          .accounts({ table: seatTablePda, creator: admin.publicKey })
+          .rpc(),
+      "pauseTable"
+    );
+    const joinPlayer3 = () =>
+      program.methods
+        .joinTable(buyInMin)
+        .accounts({
+          table: seatTablePda,
+          vault: seatVaultPda,
+          playerSeat: seatPdaFor(player3.publicKey),
+          player: player3.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([player3])
+        .rpc();
+    try {
+      await joinPlayer3();
+      throw new Error("joined a paused table");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "TablePaused") {
+        throw err;
+      }
+      console.log("Join on a paused table rejected with TablePaused");
+    }
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .unpauseTable()
+          .accounts({ table: seatTablePda, creator: admin.publicKey })
+          .rpc(),
+      "unpauseTable"
+    );
+    await sendAndConfirm(joinPlayer3, "player3 joinTable after unpause");
+
+    // The next hand is dealt to the occupied seats by index, seat 3 included
+    const [seatGamePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("game"), seatTablePda.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .startNextGame(admin.publicKey, 0)
+          .accounts({
+            table: seatTablePda,
+            game: seatGamePda,
+            backend: admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc(),
+      "startNextGame (seat reservation)"
+    );
+    const seatGame = await program.account.pokerGame.fetch(seatGamePda);
+    const reservedSeat = await program.account.playerSeat.fetch(seatPdaFor(admin.publicKey));
+    if (
+      seatGame.seatsMask !== 0b1011 ||
+      (seatGame.seatsMask & (1 << reservedSeat.seatIndex)) === 0 ||
+      seatGame.playerCount !== 3
+    ) {
+      throw new Error(
+        `hand dealt to seats ${seatGame.seatsMask.toString(2)}, expected the reserved seat 3 with 0 and 1`
+      );
+    }
+    console.log(`Reserved seat 3 dealt into the hand (seats ${seatGame.seatsMask.toString(2)})`);
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .cancelGame()
+          .accounts({
+            table: seatTablePda,
+            game: seatGamePda,
+            vault: seatVaultPda,
+            backend: admin.publicKey,
+            authority: admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc(),
+      "cancelGame (seat reservation)"
+    );
+  });
+
+  // ========================================
+  // TEST 1d: Change stakes between games
+  // ========================================
+  it("1d. New blinds apply to the next game", async () => {
+    const newSmallBlind = smallBlind.muln(2);
+    const newBigBlind = bigBlind.muln(2);
+    const stakesGameId = gameId.add(new anchor.BN(2_000));
+    const [stakesGamePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("game"),
+        tablePda.toBuffer(),
+        stakesGameId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const blindSeats = [
+      { pubkey: player2SeatPda, isWritable: true, isSigner: false },
+      { pubkey: player3SeatPda, isWritable: true, isSigner: false },
+    ];
+    const setBlinds = (sb: anchor.BN, bb: anchor.BN) =>
+      program.methods
+        .setBlinds(sb, bb)
+        .accounts({ table: tablePda, creator: admin.publicKey })
+        .rpc();
+    const startStakesGame = () =>
+      program.methods
+        .startGame(stakesGameId, admin.publicKey, 0)
+        .accounts({
+          table: tablePda,
+          game: stakesGamePda,
+          backend: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(blindSeats)
+        .rpc();
+
+    // start_game takes no blind amounts; it posts whatever the table holds
+    await sendAndConfirm(() => setBlinds(newSmallBlind, newBigBlind), "setBlinds (raise)");
+
+    const stackEventsOf = async (sig: string) => {
+      const tx = await connection.getTransaction(sig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      return [...parser.parseLogs(tx?.meta?.logMessages ?? [])]
+        .filter((e) => e.name === "stackChanged" || e.name === "StackChanged")
+        .map((e) => e.data as any);
+    };
+
+    const startSig = await sendAndConfirm(
+      startStakesGame,
+      "startGame (new blinds)"
+    );
+    const blindEvents = await stackEventsOf(startSig);
+    const posted = [newSmallBlind, newBigBlind];
+    if (blindEvents.length !== 2) {
+      throw new Error(`expected 2 StackChanged events for the blinds, got ${blindEvents.length}`);
+    }
+    blindEvents.forEach((e, i) => {
+      if (!("blind" in e.reason) || !e.chipsBefore.sub(e.chipsAfter).eq(posted[i])) {
+        throw new Error(`blind event ${i} does not match the posted blind`);
+      }
+    });
+    const stakesGame = await program.account.pokerGame.fetch(stakesGamePda);
+    if (!stakesGame.pot.eq(newSmallBlind.add(newBigBlind))) {
+      throw new Error(`pot ${stakesGame.pot.toString()}, expected the new blinds`);
+    }
+    console.log("New blinds posted, pot:", stakesGame.pot.toString());
+
+    // Blinds can't change mid-game
+    try {
+      await setBlinds(smallBlind, bigBlind);
+      throw new Error("setBlinds succeeded during a game");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "GameInProgress") {
+        throw err;
+      }
+    }
+
+    const cancelSig = await cancelAndReseat(
+      stakesGamePda,
+      [player2, player3],
+      "cancelGame (new blinds)"
+    );
+
+    // Every stack ends where it started once the blinds come back
+    const returnEvents = await stackEventsOf(cancelSig);
+    blindEvents.forEach((posted) => {
+      const returned = returnEvents.find(
+        (e) => e.seat === posted.seat && "blindReturned" in e.reason
+      );
+      if (!returned || !("blindReturned" in returned.reason) || !returned.chipsAfter.eq(posted.chipsBefore)) {
+        throw new Error(`seat ${posted.seat} stack not reconciled after cancel`);
+      }
+    });
+    console.log("StackChanged events reconcile blinds posted and returned");
+    await sendAndConfirm(() => setBlinds(smallBlind, bigBlind), "setBlinds (restore)");
+  });
+
+  // ========================================
+  // TEST 1e: Auto-numbered games
+  // ========================================
+  it("1e. start_next_game numbers games from games_played", async () => {
+    const blindSeats = [
+      { pubkey: player2SeatPda, isWritable: true, isSigner: false },
+      { pubkey: player3SeatPda, isWritable: true, isSigner: false },
+    ];
+
+    for (let i = 0; i < 3; i++) {
+      const before = (await program.account.pokerTable.fetch(tablePda)).gamesPlayed;
+      const [nextGamePda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("game"), tablePda.toBuffer(), before.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await sendAndConfirm(
+        () =>
+          program.methods
+            .startNextGame(admin.publicKey, 0)
+            .accounts({
+              table: tablePda,
+              game: nextGamePda,
+              backend: admin.publicKey,
+              systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .remainingAccounts(blindSeats)
+            .rpc(),
+        `startNextGame #${i}`
+      );
+
+      const table = await program.account.pokerTable.fetch(tablePda);
+      const game = await program.account.pokerGame.fetch(nextGamePda);
+      if (!table.gamesPlayed.eq(before.addn(1))) {
+        throw new Error(`gamesPlayed ${table.gamesPlayed.toString()}, expected ${before.addn(1).toString()}`);
+      }
+      if (!game.gameId.eq(before) || !table.currentGame?.equals(nextGamePda)) {
+        throw new Error(`game ${i} not at the PDA derived from gamesPlayed`);
+      }
+
+      // Every per-hand field starts clean, whatever earlier games did
+      const zeroHandle = (h: any) => toHandleBigInt(extractHandle(h)) === 0n;
+      const perHandReset =
+        "waiting" in game.stage &&
+        game.pot.eq(smallBlind.add(bigBlind)) &&
+        game.shuffleSeed.isZero() &&
+        zeroHandle(game.cardOffset) &&
+        game.dealCards.every(zeroHandle) &&
+        game.communityCards.every(zeroHandle) &&
+        game.dealMap.every((slot: number, j: number) => slot === j) &&
+        !game.cardsProcessed &&
+        game.cardsSubmittedMask === 0 &&
+        game.deckCommitment.every((b: number) => b === 0) &&
+        game.offsetProof.slot.isZero() &&
+        game.communityRevealed === 0 &&
+        game.winnerSeat === null &&
+        game.bestHandRank.isZero() &&
+        game.rankedMask === 0 &&
+        game.winnersMask === 0 &&
+        game.payouts.every((p: anchor.BN) => p.isZero());
+      if (!perHandReset) {
+        throw new Error(`game ${i} started with leftover per-hand state`);
+      }
+
+      await cancelAndReseat(nextGamePda, [player2, player3], `cancelGame (auto #${i})`);
+      console.log(`Auto-numbered game ${game.gameId.toString()} started and cancelled`);
+    }
+  });
+
+  // ========================================
+  // TEST 1f: Button with seat 0 empty
+  // ========================================
+  it("1f. The button lands on an occupied seat when seat 0 is empty", async () => {
+    const sparseTableId = tableId.add(new anchor.BN(4_000));
+    const [sparseTablePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("table"),
+        admin.publicKey.toBuffer(),
+        sparseTableId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [sparseVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), sparseTablePda.toBuffer()],
+      program.programId
+    );
+    const [sparseRakeVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("rake_vault"), sparseTablePda.toBuffer()],
+      program.programId
+    );
+    const seatPdaFor = (player: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("player_seat"), sparseTablePda.toBuffer(), player.toBuffer()],
+        program.programId
+      )[0];
+
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .createTable(
+            sparseTableId,
+            maxPlayers,
+            buyInMin,
+            buyInMax,
+            smallBlind,
+            bigBlind,
+            admin.publicKey,
+            null,
+            0,
+            new anchor.BN(0),
+            null,
+            0
+          )
+          .accounts({
+            table: sparseTablePda,
+            vault: sparseVaultPda,
+            rakeVault: sparseRakeVaultPda,
+            creator: admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc(),
+      "createTable (sparse seating)"
+    );
+
+    // Seats 2 and 4 only
+    for (const [player, seatIndex] of [
+      [player4, 2],
+      [player5, 4],
+    ] as [anchor.web3.Keypair, number][]) {
+      await sendAndConfirm(
+        () =>
+          program.methods
+            .joinTableSeat(buyInMin, seatIndex)
+            .accounts({
+              table: sparseTablePda,
+              vault: sparseVaultPda,
+              playerSeat: seatPdaFor(player.publicKey),
+              player: player.publicKey,
+              systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([player])
+            .rpc(),
+        `joinTableSeat(${seatIndex})`
+      );
+    }
+
+    const [sparseGamePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("game"), sparseTablePda.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const blindSeats = [
+      { pubkey: seatPdaFor(player4.publicKey), isWritable: true, isSigner: false },
+      { pubkey: seatPdaFor(player5.publicKey), isWritable: true, isSigner: false },
+    ];
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .startNextGame(admin.publicKey, 0)
+          .accounts({
+            table: sparseTablePda,
+            game: sparseGamePda,
+            backend: admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts(blindSeats)
+          .rpc(),
+      "startNextGame (sparse seating)"
+    );
+
+    const sparseGame = await program.account.pokerGame.fetch(sparseGamePda);
+    const snapshot = await program.methods
+      .getSnapshot()
+      .accounts({ table: sparseTablePda, game: sparseGamePda })
+      .view();
+    if (sparseGame.dealerSeat !== 2 || snapshot.dealerSeat !== 2 || sparseGame.seatsMask !== 0b10100) {
+      throw new Error(
+        `button on seat ${sparseGame.dealerSeat} (mask ${sparseGame.seatsMask}), expected seat 2`
+      );
+    }
+    console.log("Button skipped empty seat 0 and landed on seat", sparseGame.dealerSeat);
+
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .cancelGame()
+          .accounts({
+            table: sparseTablePda,
+            game: sparseGamePda,
+            vault: sparseVaultPda,
+            backend: admin.publicKey,
+            authority: admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts(
+            [player4, player5].flatMap((player) => [
+              { pubkey: seatPdaFor(player.publicKey), isWritable: true, isSigner: false },
+              { pubkey: player.publicKey, isWritable: true, isSigner: false },
+            ])
+          )
+          .rpc(),
+      "cancelGame (sparse seating)"
+    );
+  });
+
+  // ========================================
+  // TEST 1g: SPL token table round trip
+  // ========================================
+  it("1g. Token table: buy in, refuse native funds, refund in tokens", async () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const tokenTableId = tableId.add(new anchor.BN(5_000));
+    const [tokenTablePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("table"),
+        admin.publicKey.toBuffer(),
+        tokenTableId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [tokenVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), tokenTablePda.toBuffer()],
+      program.programId
+    );
+    const [tokenRakeVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("rake_vault"), tokenTablePda.toBuffer()],
+      program.programId
+    );
+    const seatPdaFor = (player: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("player_seat"), tokenTablePda.toBuffer(), player.toBuffer()],
+        program.programId
+      )[0];
+
+    // A 6-decimal "USDC" mint with the vault PDA owning the table's token account
+    const mint = await createMint(connection, payer, admin.publicKey, null, 6);
+    const vaultToken = await getOrCreateAssociatedTokenAccount(
+      connection,
+      payer,
+      mint,
+      tokenVaultPda,
+      true
+    );
+    const players = [player2, player3];
+    const playerTokens: anchor.web3.PublicKey[] = [];
+    for (const player of players) {
+      const ata = await getOrCreateAssociatedTokenAccount(connection, payer, mint, player.publicKey);
+      await mintTo(connection, payer, mint, ata.address, payer, BigInt(playerBuyIn.toString()));
+      playerTokens.push(ata.address);
+    }
+
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .createTable(
+            tokenTableId,
+            maxPlayers,
+            buyInMin,
+            buyInMax,
+            smallBlind,
+            bigBlind,
+            admin.publicKey,
+            mint,
+            0,
+            new anchor.BN(0),
+            null,
+            0
+          )
+          .accounts({
+            table: tokenTablePda,
+            vault: tokenVaultPda,
+            rakeVault: tokenRakeVaultPda,
+            creator: admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc(),
+      "createTable (token)"
+    );
+
+    const joinToken = (i: number, withTokens: boolean) =>
+      program.methods
+        .joinTable(playerBuyIn)
+        .accounts({
+          table: tokenTablePda,
+          vault: tokenVaultPda,
+          playerSeat: seatPdaFor(players[i].publicKey),
+          player: players[i].publicKey,
+          playerTokenAccount: withTokens ? playerTokens[i] : null,
+          vaultTokenAccount: withTokens ? vaultToken.address : null,
+          tokenProgram: withTokens ? TOKEN_PROGRAM_ID : null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([players[i]])
+        .rpc();
+
+    // A token table never takes lamports
+    try {
+      await joinToken(0, false);
+      throw new Error("token table accepted a native buy-in");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "MixedFundingMode") {
+        throw err;
+      }
+      console.log("Native buy-in on a token table rejected with MixedFundingMode");
+    }
+
+    for (let i = 0; i < players.length; i++) {
+      await sendAndConfirm(() => joinToken(i, true), `player${i + 2} joinTable (token)`);
+    }
+    const vaultHeld = (await getAccount(connection, vaultToken.address)).amount;
+    if (vaultHeld !== BigInt(playerBuyIn.muln(2).toString())) {
+      throw new Error(`token vault holds ${vaultHeld}, expected both buy-ins`);
+    }
+    for (const ata of playerTokens) {
+      if ((await getAccount(connection, ata)).amount !== 0n) {
+        throw new Error("buy-in left tokens in the player's account");
+      }
+    }
+    console.log("Both buy-ins landed in the vault token account:", vaultHeld.toString());
+
+    const closeTokenTable = () =>
+      program.methods
+        .closeTable()
+        .accounts({
+          table: tokenTablePda,
+          vault: tokenVaultPda,
+          vaultTokenAccount: vaultToken.address,
+          rakeVault: tokenRakeVaultPda,
+          rakeTokenAccount: null,
+          creatorTokenAccount: null,
+          creator: admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    // Seated players hold the table open
+    try {
+      await closeTokenTable();
+      throw new Error("closeTable closed a table with seated players");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "TableNotEmpty") {
+        throw err;
+      }
+      console.log("Close with seated players rejected with TableNotEmpty");
+    }
+
+    // Start a game without blinds, then refund every stack in tokens
+    const [tokenGamePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("game"), tokenTablePda.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .startNextGame(admin.publicKey, 0)
+          .accounts({
+            table: tokenTablePda,
+            game: tokenGamePda,
+            backend: admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc(),
+      "startNextGame (token)"
+    );
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .refundAll()
+          .accounts({
+            table: tokenTablePda,
+            game: tokenGamePda,
+            vault: tokenVaultPda,
+            vaultTokenAccount: vaultToken.address,
+            backend: admin.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts(
+            players.flatMap((player, i) => [
+              { pubkey: seatPdaFor(player.publicKey), isWritable: true, isSigner: false },
+              { pubkey: player.publicKey, isWritable: true, isSigner: false },
+              { pubkey: playerTokens[i], isWritable: true, isSigner: false },
+            ])
+          )
+          .rpc(),
+      "refundAll (token)"
+    );
+
+    for (const ata of playerTokens) {
+      const refunded = (await getAccount(connection, ata)).amount;
+      if (refunded !== BigInt(playerBuyIn.toString())) {
+        throw new Error(`refund returned ${refunded} tokens, expected the full buy-in`);
+      }
+    }
+    if ((await getAccount(connection, vaultToken.address)).amount !== 0n) {
+      throw new Error("token vault not emptied by refundAll");
+    }
+    const tokenTable = await program.account.pokerTable.fetch(tokenTablePda);
+    if (tokenTable.playerCount !== 0 || tokenTable.seatsTaken !== 0) {
+      throw new Error("refundAll left players seated at the token table");
+    }
+    console.log("Token stacks refunded in full, vault emptied");
+
+    // Once empty the table closes along with its vault token account
+    await sendAndConfirm(closeTokenTable, "closeTable (token)");
+    if (await program.account.pokerTable.fetchNullable(tokenTablePda)) {
+      throw new Error("token table still open after closeTable");
+    }
+    if (await connection.getAccountInfo(vaultToken.address)) {
+      throw new Error("vault token account not closed with the table");
+    }
+    console.log("Empty token table closed with its vault token account");
+  });
+
+  it("1h. Closing an empty table sweeps the vault and the rake vault", async () => {
+    const closeTableId = tableId.add(new anchor.BN(6_000));
+    const [closeTablePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("table"),
+        admin.publicKey.toBuffer(),
+        closeTableId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [closeVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), closeTablePda.toBuffer()],
+      program.programId
+    );
+    const [closeRakeVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("rake_vault"), closeTablePda.toBuffer()],
+      program.programId
+    );
+
+    // A raked table funds its rake vault at creation
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .createTable(
+            closeTableId,
+            maxPlayers,
+            buyInMin,
+            buyInMax,
+            smallBlind,
+            bigBlind,
+            admin.publicKey,
+            null,
+            rakeBps,
+            new anchor.BN(0),
+            null,
+            0
+          )
+          .accounts({
+            table: closeTablePda,
+            vault: closeVaultPda,
+            rakeVault: closeRakeVaultPda,
+            creator: admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc(),
+      "createTable (to close)"
+    );
+
+    // Stand-in for rake that was never withdrawn
+    const unclaimedRake = 1_000_000;
+    await sendAndConfirm(
+      () =>
+        provider.sendAndConfirm(
+          new anchor.web3.Transaction().add(
+            anchor.web3.SystemProgram.transfer({
+              fromPubkey: admin.publicKey,
+              toPubkey: closeRakeVaultPda,
+              lamports: unclaimedRake,
+            })
+          )
+        ),
+      "fund rake vault"
+    );
+    const rakeHeld = await connection.getBalance(closeRakeVaultPda);
+    if (rakeHeld <= unclaimedRake) {
+      throw new Error(`rake vault holds ${rakeHeld}, expected rent plus the rake`);
+    }
+
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .closeTable()
+          .accounts({
+            table: closeTablePda,
+            vault: closeVaultPda,
+            vaultTokenAccount: null,
+            rakeVault: closeRakeVaultPda,
+            rakeTokenAccount: null,
+            creatorTokenAccount: null,
+            creator: admin.publicKey,
+            tokenProgram: null,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc(),
+      "closeTable (empty)"
+    );
+
+    if (await program.account.pokerTable.fetchNullable(closeTablePda)) {
+      throw new Error("empty table still open after closeTable");
+    }
+    const leftInVaults =
+      (await connection.getBalance(closeVaultPda)) +
+      (await connection.getBalance(closeRakeVaultPda));
+    if (leftInVaults !== 0) {
+      throw new Error(`${leftInVaults} lamports left behind in the table's vaults`);
+    }
+    console.log(`Empty table closed, ${rakeHeld} lamports swept from the rake vault`);
+  });
+
+  it("1i. Top up a stack to buy_in_max, never past it, never mid-game", async () => {
+    const topUpTableId = tableId.add(new anchor.BN(7_000));
+    const topUpBuyInMax = new anchor.BN(10_000_000);
+    const startingStack = new anchor.BN(5_000_000);
+    const [topUpTablePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("table"),
+        admin.publicKey.toBuffer(),
+        topUpTableId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [topUpVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), topUpTablePda.toBuffer()],
+      program.programId
+    );
+    const [topUpRakeVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("rake_vault"), topUpTablePda.toBuffer()],
+      program.programId
+    );
+    const seatPdaFor = (player: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("player_seat"), topUpTablePda.toBuffer(), player.toBuffer()],
+        program.programId
+      )[0];
+    const players = [player4, player5];
+
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .createTable(
+            topUpTableId,
+            maxPlayers,
+            buyInMin,
+            topUpBuyInMax,
+            smallBlind,
+            bigBlind,
+            admin.publicKey,
+            null,
+            0,
+            new anchor.BN(0),
+            null,
+            0
+          )
+          .accounts({
+            table: topUpTablePda,
+            vault: topUpVaultPda,
+            rakeVault: topUpRakeVaultPda,
+            creator: admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc(),
+      "createTable (top-up)"
+    );
+    for (const player of players) {
+      await sendAndConfirm(
+        () =>
+          program.methods
+            .joinTable(startingStack)
+            .accounts({
+              table: topUpTablePda,
+              vault: topUpVaultPda,
+              playerSeat: seatPdaFor(player.publicKey),
+              player: player.publicKey,
+              systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([player])
+            .rpc(),
+        "joinTable (top-up)"
+      );
+    }
+
+    const addChips = (player: anchor.web3.Keypair, amount: anchor.BN) =>
+      program.methods
+        .addChips(amount)
+        .accounts({
+          table: topUpTablePda,
+          vault: topUpVaultPda,
+          playerSeat: seatPdaFor(player.publicKey),
+          player: player.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+    const expectError = async (fn: () => Promise<string>, code: string, what: string) => {
+      try {
+        await fn();
+        throw new Error(`${what} was accepted`);
+      } catch (err: any) {
+        if (err?.error?.errorCode?.code !== code) {
+          throw err;
+        }
+        console.log(`${what} rejected with ${code}`);
+      }
+    };
+
+    // Topping up exactly to buy_in_max is allowed and lands in the vault
+    const vaultBefore = await connection.getBalance(topUpVaultPda);
+    const topUp = topUpBuyInMax.sub(startingStack);
+    await sendAndConfirm(() => addChips(player4, topUp), "addChips to buy_in_max");
+    const seat = await program.account.playerSeat.fetch(seatPdaFor(player4.publicKey));
+    const vaultGain = (await connection.getBalance(topUpVaultPda)) - vaultBefore;
+    if (!seat.chips.eq(topUpBuyInMax) || vaultGain !== topUp.toNumber()) {
+      throw new Error(`stack ${seat.chips.toString()} (vault +${vaultGain}) after topping up to the max`);
+    }
+    console.log("player4 topped up to buy_in_max:", seat.chips.toString());
+
+    // Even one lamport over the cap is refused
+    await expectError(() => addChips(player4, new anchor.BN(1)), "InvalidBuyIn", "Top-up past buy_in_max");
+
+    // No top-ups while a hand is live
+    const [topUpGamePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("game"), topUpTablePda.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .startNextGame(admin.publicKey, 0)
+          .accounts({
+            table: topUpTablePda,
+            game: topUpGamePda,
+            backend: admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc(),
+      "startNextGame (top-up)"
+    );
+    await expectError(() => addChips(player5, new anchor.BN(1_000_000)), "GameInProgress", "Mid-game top-up");
+
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .refundAll()
+          .accounts({
+            table: topUpTablePda,
+            game: topUpGamePda,
+            vault: topUpVaultPda,
+            backend: admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts(
+            players.flatMap((player) => [
+              { pubkey: seatPdaFor(player.publicKey), isWritable: true, isSigner: false },
+              { pubkey: player.publicKey, isWritable: true, isSigner: false },
+            ])
+          )
+          .rpc(),
+      "refundAll (top-up)"
+    );
+  });
+
+  // ========================================
+  // TEST 1j: Blinds on 2- and 3-handed tables
+  // ========================================
+  it("1j. Small tables post two distinct blinds into the pot", async () => {
+    const stack = new anchor.BN(5_000_000);
+    const cases = [
+      { label: "heads-up", offset: 8_000, players: [player4, player5] },
+      { label: "3-handed", offset: 8_100, players: [player3, player4, player5] },
+    ];
+
+    for (const { label, offset, players } of cases) {
+      const smallTableId = tableId.add(new anchor.BN(offset));
+      const [smallTablePda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("table"),
+          admin.publicKey.toBuffer(),
+          smallTableId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      const [smallVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), smallTablePda.toBuffer()],
+        program.programId
+      );
+      const [smallRakeVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("rake_vault"), smallTablePda.toBuffer()],
+        program.programId
+      );
+      const [smallGamePda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("game"), smallTablePda.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const seatPdaFor = (player: anchor.web3.PublicKey) =>
+        anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("player_seat"), smallTablePda.toBuffer(), player.toBuffer()],
+          program.programId
+        )[0];
+      const seatMeta = (pubkey: anchor.web3.PublicKey) => ({ pubkey, isWritable: true, isSigner: false });
+
+      await sendAndConfirm(
+        () =>
+          program.methods
+            .createTable(
+              smallTableId,
+              maxPlayers,
+              buyInMin,
+              buyInMax,
+              smallBlind,
+              bigBlind,
+              admin.publicKey,
+              null,
+              0,
+              new anchor.BN(0),
+              null,
+              0
+            )
+            .accounts({
+              table: smallTablePda,
+              vault: smallVaultPda,
+              rakeVault: smallRakeVaultPda,
+              creator: admin.publicKey,
+              systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc(),
+        `createTable (${label})`
+      );
+      for (const player of players) {
+        await sendAndConfirm(
+          () =>
+            program.methods
+              .joinTable(stack)
+              .accounts({
+                table: smallTablePda,
+                vault: smallVaultPda,
+                playerSeat: seatPdaFor(player.publicKey),
+                player: player.publicKey,
+                systemProgram: anchor.web3.SystemProgram.programId,
+              })
+              .signers([player])
+              .rpc(),
+          `joinTable (${label})`
+        );
+      }
+
+      // Heads-up the button posts the small blind; 3-handed it sits out the blinds
+      const sbPlayer = players.length === 2 ? players[0] : players[1];
+      const bbPlayer = players.length === 2 ? players[1] : players[2];
+      const startSmallGame = (blindSeats: anchor.web3.PublicKey[]) =>
+        program.methods
+          .startGame(new anchor.BN(0), admin.publicKey, 0)
+          .accounts({
+            table: smallTablePda,
+            game: smallGamePda,
+            backend: admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts(blindSeats.map(seatMeta))
+          .rpc();
+
+      // The same seat can't be charged both blinds, even where positions wrap
+      try {
+        await startSmallGame([seatPdaFor(bbPlayer.publicKey), seatPdaFor(bbPlayer.publicKey)]);
+        throw new Error(`${label}: one seat posted both blinds`);
+      } catch (err: any) {
+        if (err?.error?.errorCode?.code !== "InvalidSeatIndex") {
+          throw err;
+        }
+        console.log(`${label}: same seat for both blinds rejected with InvalidSeatIndex`);
+      }
+
+      // A seat from another table can't post a blind here
+      try {
+        await startSmallGame([player2SeatPda, seatPdaFor(bbPlayer.publicKey)]);
+        throw new Error(`${label}: a seat from another table posted a blind`);
+      } catch (err: any) {
+        if (err?.error?.errorCode?.code !== "PlayerNotAtTable") {
+          throw err;
+        }
+        console.log(`${label}: foreign seat rejected with PlayerNotAtTable`);
+      }
+
+      await sendAndConfirm(
+        () => startSmallGame([seatPdaFor(sbPlayer.publicKey), seatPdaFor(bbPlayer.publicKey)]),
+        `startGame (${label})`
+      );
+      const game = await program.account.pokerGame.fetch(smallGamePda);
+      const sbSeat = await program.account.playerSeat.fetch(seatPdaFor(sbPlayer.publicKey));
+      const bbSeat = await program.account.playerSeat.fetch(seatPdaFor(bbPlayer.publicKey));
+      if (
+        !game.pot.eq(smallBlind.add(bigBlind)) ||
+        game.playerCount !== players.length ||
+        !sbSeat.chips.eq(stack.sub(smallBlind)) ||
+        !bbSeat.chips.eq(stack.sub(bigBlind))
+      ) {
+        throw new Error(
+          `${label}: pot ${game.pot.toString()}, stacks ${sbSeat.chips.toString()}/${bbSeat.chips.toString()}`
+        );
+      }
+      console.log(`${label}: seats ${sbSeat.seatIndex}/${bbSeat.seatIndex} posted, pot ${game.pot.toString()}`);
+
+      await sendAndConfirm(
+        () =>
+          program.methods
+            .refundAll()
+            .accounts({
+              table: smallTablePda,
+              game: smallGamePda,
+              vault: smallVaultPda,
+              backend: admin.publicKey,
+              systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .remainingAccounts(
+              players.flatMap((player) => [
+                seatMeta(seatPdaFor(player.publicKey)),
+                seatMeta(player.publicKey),
+              ])
+            )
+            .rpc(),
+        `refundAll (${label})`
+      );
+    }
+  });
+
+  // ========================================
+  // TEST 2: Start game with blind bets
+  // ========================================
+  it("2. Start game with blind bets", async () => {
+    // Indexers follow the hand from events, so listen for the blinds being posted
+    let blindsListener: number | null = null;
+    const blindsPosted = new Promise<any>((resolve) => {
+      blindsListener = program.addEventListener("blindsPosted", (event) => {
+        if (event.game.equals(gamePda)) {
+          resolve(event);
+        }
+      });
+    });
+
+    // Small blind = seat 1 (player2), Big blind = seat 2 (player3)
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .startGame(gameId, admin.publicKey, 0)
+          .accounts({
+            table: tablePda,
+            game: gamePda,
+            admin: admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts([
+            { pubkey: player2SeatPda, isWritable: true, isSigner: false },
+            { pubkey: player3SeatPda, isWritable: true, isSigner: false },
+          ])
+          .rpc(),
+      "startGame"
+    );
+
+    const game = await program.account.pokerGame.fetch(gamePda);
+    console.log("Game started:");
+    console.log("  Game ID:", game.gameId.toString());
+    console.log("  Stage:", game.stage);
+    console.log("  Initial pot (blinds):", game.pot.toString());
+    if (!game.pot.eq(smallBlind.add(bigBlind))) {
+      throw new Error("Pot should hold the configured small + big blind");
+    }
+    const bbSeat = await program.account.playerSeat.fetch(player3SeatPda);
+    if (!bbSeat.currentBet.eq(bigBlind)) {
+      throw new Error("Big blind seat should have the configured big blind in");
+    }
+    console.log("  Player count:", game.playerCount);
+
+    const event = await Promise.race([blindsPosted, sleep(30_000).then(() => null)]);
+    await program.removeEventListener(blindsListener!);
+    if (!event) {
+      throw new Error("no BlindsPosted event seen for the game");
+    }
+    if (
+      event.sbSeat !== 1 ||
+      event.bbSeat !== 2 ||
+      !event.sb.eq(smallBlind) ||
+      !event.bb.eq(bigBlind)
+    ) {
+      throw new Error(
+        `BlindsPosted seats ${event.sbSeat}/${event.bbSeat} amounts ${event.sb}/${event.bb}`
+      );
+    }
+    console.log("  BlindsPosted event: seats", event.sbSeat, event.bbSeat);
+  });
+
+  // ========================================
+  // TEST 3: Process cards (8 batches)
+  // ========================================
+  it("3. Process cards (8 batches)", async () => {
+    console.log("Card values to encrypt:", CARD_VALUES);
+
+    const encryptedCards: Buffer[] = [];
+    for (let i = 0; i < 15; i++) {
+      const hex = await encryptValue(BigInt(CARD_VALUES[i]));
+      encryptedCards.push(Buffer.from(hex, "hex"));
+      if (i < 4) {
+        console.log(`  card[${i}] value=${CARD_VALUES[i]} encrypted`);
+      }
+    }
+
+    // Batch 0 seeds the shuffle and must go first; the rest may land in any order
+    const batchOrder = [0, 7, 6, 5, 4, 3, 2, 1];
+    const submittedBatches: Array<{ batch: number; card0: Buffer; card1: Buffer }> = [];
+    for (const batch of batchOrder) {
+      const idx0 = batch * 2;
+      const idx1 = batch * 2 + 1;
+      const card0 = encryptedCards[idx0] || encryptedCards[0];
+      const card1 =
+        idx1 < 15
+          ? encryptedCards[idx1]
+          : Buffer.from(await encryptValue(0n), "hex");
+
+      await sendAndConfirm(
+        () =>
+          program.methods
+            .processCardsBatch(batch, card0, card1, 0)
+            .accounts({
+              table: tablePda,
+              game: gamePda,
+              backend: admin.publicKey,
+              slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+              incoLightningProgram: INCO_LIGHTNING_ID,
+              systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc(),
+        `processCardsBatch(${batch})`
+      );
+      submittedBatches.push({ batch, card0, card1 });
+
+      if (batch === 0) {
+        // An empty ciphertext never reaches Inco as a card
+        try {
+          await program.methods
+            .processCardsBatch(1, Buffer.alloc(0), encryptedCards[3], 0)
+            .accounts({
+              table: tablePda,
+              game: gamePda,
+              backend: admin.publicKey,
+              slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+              incoLightningProgram: INCO_LIGHTNING_ID,
+              systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc();
+          throw new Error("processCardsBatch accepted an empty card");
+        } catch (err: any) {
+          if (err?.error?.errorCode?.code !== "InvalidCardCount") {
+            throw err;
+          }
+          console.log("Empty card rejected with InvalidCardCount");
+        }
+      }
+
+      if (batch === 0 || batch === 1) {
+        await logCardOffset(`after batch ${batch}`);
+      }
+
+      if (batch === 7) {
+        // Submission progress is tracked apart from street reveals
+        const partial = await program.account.pokerGame.fetch(gamePda);
+        // Batch 0 holds cards 0-1, batch 7 only card 14
+        if (partial.cardsSubmittedMask !== 0b100_0000_0000_0011) {
+          throw new Error(`unexpected cards_submitted_mask ${partial.cardsSubmittedMask}`);
+        }
+        if (partial.communityRevealed !== 0 || partial.cardsProcessed) {
+          throw new Error("batch 7 out of order must not finalize or reveal");
+        }
+      }
+    }
+
+    const game = await program.account.pokerGame.fetch(gamePda);
+    console.log("\nCards processed:");
+    console.log("  cards_processed:", game.cardsProcessed);
+    console.log("  stage:", game.stage);
+    console.log("  shuffle_seed:", game.shuffleSeed.toString());
+    console.log("  shuffled_indices:", game.shuffledIndices);
+    console.log("  deal_map:", game.dealMap);
+    console.log("  cards_submitted_mask:", game.cardsSubmittedMask);
+    if (game.cardsSubmittedMask !== 0x7fff || game.communityRevealed !== 0) {
+      throw new Error("expected all cards submitted and no streets revealed");
+    }
+
+    // Replay the deck commitment from the submitted ciphertexts
+    const batchHash = (batch: number, card0: Buffer, card1: Buffer) => {
+      const len = Buffer.alloc(4);
+      len.writeUInt32LE(card0.length);
+      return Array.from(
+        keccak_256(Buffer.concat([Buffer.from([batch]), len, card0, card1]))
+      );
+    };
+    const verifyDeck = (hashes: number[][]) =>
+      program.methods
+        .verifyDeckCommitment(hashes)
+        .accounts({ table: tablePda, game: gamePda })
+        .rpc();
+
+    await sendAndConfirm(
+      () => verifyDeck(submittedBatches.map((b) => batchHash(b.batch, b.card0, b.card1))),
+      "verifyDeckCommitment"
+    );
+
+    // Flipping a single ciphertext byte must break the commitment
+    const tampered = submittedBatches.map((b, i) => {
+      if (i !== 3) return batchHash(b.batch, b.card0, b.card1);
+      const card0 = Buffer.from(b.card0);
+      card0[0] ^= 0x01;
+      return batchHash(b.batch, card0, b.card1);
+    });
+    try {
+      await verifyDeck(tampered);
+      throw new Error("tampered deck matched the commitment");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "DeckCommitmentMismatch") {
+        throw err;
+      }
+      console.log("Tampered deck rejected with DeckCommitmentMismatch");
+    }
+
+    // The recorded offset recomputes from its slot hash; a tampered one doesn't
+    const gameForOffset = await program.account.pokerGame.fetch(gamePda);
+    const recordedOffset = gameForOffset.offsetProof.offset;
+    if (recordedOffset > 51) {
+      throw new Error(`offset ${recordedOffset} out of range`);
+    }
+    const verifyOffset = (offset: number) =>
+      program.methods
+        .verifyOffset(offset)
+        .accounts({ table: tablePda, game: gamePda })
+        .rpc();
+    await sendAndConfirm(() => verifyOffset(recordedOffset), "verifyOffset");
+    try {
+      await verifyOffset((recordedOffset + 1) % 52);
+      throw new Error("tampered offset passed verification");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "OffsetMismatch") {
+        throw err;
+      }
+      console.log("Tampered offset rejected with OffsetMismatch");
+    }
+
+    // Retrying a batch that already landed succeeds without changing anything
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .processCardsBatch(0, encryptedCards[0], encryptedCards[1], 0)
+          .accounts({
+            table: tablePda,
+            game: gamePda,
+            backend: admin.publicKey,
+            slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+            incoLightningProgram: INCO_LIGHTNING_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc(),
+      "processCardsBatch(0) resubmit"
+    );
+    const afterRetry = await program.account.pokerGame.fetch(gamePda);
+    if (
+      !afterRetry.shuffleSeed.eq(game.shuffleSeed) ||
+      !Buffer.from(afterRetry.deckCommitment).equals(Buffer.from(game.deckCommitment)) ||
+      afterRetry.cardsSubmittedMask !== game.cardsSubmittedMask
+    ) {
+      throw new Error("resubmitting batch 0 changed the deal");
+    }
+
+    // An input type Inco doesn't accept is refused up front
+    try {
+      await program.methods
+        .processCardsBatch(0, encryptedCards[0], encryptedCards[1], 7)
+        .accounts({
+          table: tablePda,
+          game: gamePda,
+          backend: admin.publicKey,
+          slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+          incoLightningProgram: INCO_LIGHTNING_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      throw new Error("processCardsBatch accepted input_type 7");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "InvalidInputType") {
+        throw err;
+      }
+      console.log("input_type 7 rejected with InvalidInputType");
+    }
+
+    // Every submitted card must land in its own slot: no hole card can
+    // share a position with a community card
+    const dealtSlots = new Set<number>(game.dealMap);
+    if (dealtSlots.size !== 15 || [...dealtSlots].some((s) => s < 0 || s > 14)) {
+      throw new Error(`deal_map is not a permutation of 0..15: ${game.dealMap}`);
+    }
+  });
+
+  // ========================================
+  // TEST 4: Reveal hands (allow decryption)
+  // ========================================
+  it("4. Reveal all hands (allow decryption)", async () => {
+    const game = await program.account.pokerGame.fetch(gamePda);
+    const players = [
+      {
+        label: "admin",
+        publicKey: admin.publicKey,
+        seatPda: adminSeatPda,
+        signMessage: getSignMessage(),
+      },
+      {
+        label: "player2",
+        publicKey: player2.publicKey,
+        seatPda: player2SeatPda,
+        signMessage: getKeypairSignMessage(player2),
+      },
+      {
+        label: "player3",
+        publicKey: player3.publicKey,
+        seatPda: player3SeatPda,
+        signMessage: getKeypairSignMessage(player3),
+      },
+      {
+        label: "player4",
+        publicKey: player4.publicKey,
+        seatPda: player4SeatPda,
+        signMessage: getKeypairSignMessage(player4),
+      },
+      {
+        label: "player5",
+        publicKey: player5.publicKey,
+        seatPda: player5SeatPda,
+        signMessage: getKeypairSignMessage(player5),
+      },
+    ];
+
+    decryptedHoleCardsBySeat = [];
+
+    const dealt = [];
+    for (const player of players) {
+      const seat = await program.account.playerSeat.fetch(player.seatPda);
+      const seatIndex = seat.seatIndex as number;
+      const pairIndex = game.shuffledIndices.findIndex(
+        (s: number) => s === seatIndex
+      );
+      if (pairIndex === -1) {
+        console.log(
+          `${player.label}: seat ${seatIndex} not found in shuffled indices`
+        );
+        continue;
+      }
+
+      const h1 = extractHandle(game.dealCards[pairIndex * 2]);
+      const h2 = extractHandle(game.dealCards[pairIndex * 2 + 1]);
+
+      const allowance1 = anchor.web3.PublicKey.findProgramAddressSync(
+        [handleToBytesLE(h1), player.publicKey.toBuffer()],
+        INCO_LIGHTNING_ID
+      )[0];
+      const allowance2 = anchor.web3.PublicKey.findProgramAddressSync(
+        [handleToBytesLE(h2), player.publicKey.toBuffer()],
+        INCO_LIGHTNING_ID
+      )[0];
+
+      dealt.push({ player, seatIndex, h1, h2, allowance1, allowance2 });
+    }
+
+    // Admin reveals alone; the other four seats get all eight allows in one call
+    const [adminDeal, ...otherDeals] = dealt;
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .revealHand()
+          .accounts({
+            table: tablePda,
+            game: gamePda,
+            playerSeat: adminDeal.player.seatPda,
+            player: adminDeal.player.publicKey,
+            backend: admin.publicKey,
+            incoLightningProgram: INCO_LIGHTNING_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts([
+            { pubkey: adminDeal.allowance1, isWritable: true, isSigner: false },
+            { pubkey: adminDeal.allowance2, isWritable: true, isSigner: false },
+          ])
+          .rpc(),
+      `revealHand(${adminDeal.player.label})`
+    );
+
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .batchAllowHoleCards()
+          .accounts({
+            table: tablePda,
+            game: gamePda,
+            backend: admin.publicKey,
+            incoLightningProgram: INCO_LIGHTNING_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts(
+            otherDeals.flatMap((d) => [
+              { pubkey: d.player.seatPda, isWritable: false, isSigner: false },
+              { pubkey: d.player.publicKey, isWritable: false, isSigner: false },
+              { pubkey: d.allowance1, isWritable: true, isSigner: false },
+              { pubkey: d.allowance2, isWritable: true, isSigner: false },
+            ])
+          )
+          .preInstructions([
+            anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+              units: 1_400_000,
+            }),
+          ])
+          .rpc(),
+      `batchAllowHoleCards(${otherDeals.length} seats)`
+    );
+
+    for (const { player, seatIndex, h1, h2 } of dealt) {
+      if (player.signMessage) {
+        const handles = [handleToDecimalString(h1), handleToDecimalString(h2)];
+        try {
           const result = await decryptWithRetry(
             handles,
             player.publicKey,
@@ -669,45 +2404,772 @@ async function sendAndConfirm(fn: () => Promise<string>, desc: string) {
     }
   });
 
+  // ========================================
+  // TEST 5a: Open the flop and let a spectator watch it
+  // ========================================
+  it("5a. Spectator sees the flop but not the unrevealed turn", async () => {
+    const game = await program.account.pokerGame.fetch(gamePda);
+    const spectator = anchor.web3.Keypair.generate();
+    const allowanceFor = (cardIdx: number, who: anchor.web3.PublicKey) => ({
+      pubkey: anchor.web3.PublicKey.findProgramAddressSync(
+        [handleToBytesLE(extractHandle(game.communityCards[cardIdx])), who.toBuffer()],
+        INCO_LIGHTNING_ID
+      )[0],
+      isWritable: true,
+      isSigner: false,
+    });
+
+    const flopSig = await sendAndConfirm(
+      () =>
+        program.methods
+          .revealStreet({ flop: {} })
+          .accounts({
+            table: tablePda,
+            game: gamePda,
+            backend: admin.publicKey,
+            incoLightningProgram: INCO_LIGHTNING_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts([
+            { pubkey: adminSeatPda, isWritable: false, isSigner: false },
+            { pubkey: admin.publicKey, isWritable: false, isSigner: false },
+            ...[0, 1, 2].map((i) => allowanceFor(i, admin.publicKey)),
+          ])
+          .rpc(),
+      "revealStreet(flop)"
+    );
+
+    // One seat listed, so the flop must grant exactly its three cards
+    const flopTx = await provider.connection.getTransaction(flopSig, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const flopKeys = flopTx!.transaction.message.getAccountKeys({
+      accountKeysFromLookups: flopTx!.meta!.loadedAddresses,
+    });
+    const allowCpis = (flopTx!.meta!.innerInstructions ?? [])
+      .flatMap((inner) => inner.instructions)
+      .filter((ix) => flopKeys.get(ix.programIdIndex)?.equals(INCO_LIGHTNING_ID)).length;
+    if (allowCpis !== 3) {
+      throw new Error(`flop issued ${allowCpis} allow CPIs, expected 3`);
+    }
+    console.log("Flop issued", allowCpis, "allow CPIs");
+
+    // The snapshot view mirrors the game account in a single call
+    const snapshot = await program.methods
+      .getSnapshot()
+      .accounts({ table: tablePda, game: gamePda })
+      .view();
+    const gameNow = await program.account.pokerGame.fetch(gamePda);
+    if (
+      !snapshot.pot.eq(gameNow.pot) ||
+      snapshot.playerCount !== gameNow.playerCount ||
+      snapshot.cardsProcessed !== true ||
+      snapshot.communityRevealed !== 0b111 ||
+      snapshot.communityRevealed !== gameNow.communityRevealed ||
+      !("playing" in snapshot.stage)
+    ) {
+      throw new Error(`snapshot out of sync with the game: ${JSON.stringify(snapshot)}`);
+    }
+    console.log("Snapshot after the flop:", JSON.stringify(snapshot));
+
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .allowSpectator({ flop: {} })
+          .accounts({
+            table: tablePda,
+            game: gamePda,
+            spectator: spectator.publicKey,
+            backend: admin.publicKey,
+            incoLightningProgram: INCO_LIGHTNING_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts([0, 1, 2].map((i) => allowanceFor(i, spectator.publicKey)))
+          .rpc(),
+      "allowSpectator(flop)"
+    );
+
+    const flop = await decryptWithRetry(
+      [0, 1, 2].map((i) => handleToDecimalString(game.communityCards[i])),
+      spectator.publicKey,
+      getKeypairSignMessage(spectator),
+      "spectator flop"
+    );
+    console.log("Spectator flop:", flop.plaintexts.map(toCardIndex));
+
+    try {
+      await program.methods
+        .allowSpectator({ turn: {} })
+        .accounts({
+          table: tablePda,
+          game: gamePda,
+          spectator: spectator.publicKey,
+          backend: admin.publicKey,
+          incoLightningProgram: INCO_LIGHTNING_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([allowanceFor(3, spectator.publicKey)])
+        .rpc();
+      throw new Error("spectator was allowed the unrevealed turn");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "CardNotRevealed") {
+        throw err;
+      }
+      console.log("Turn withheld from spectator with CardNotRevealed");
+    }
+  });
+
+  // ========================================
+  // TEST 5b: Show or muck at showdown
+  // ========================================
+  it("5b. Mucked hands are never opened to spectators", async () => {
+    const game = await program.account.pokerGame.fetch(gamePda);
+    const spectator = anchor.web3.Keypair.generate();
+
+    const declare = (seatPda: anchor.web3.PublicKey, player: any, choice: any, signers: any[]) =>
+      program.methods
+        .declareShowdownAction(choice)
+        .accounts({
+          table: tablePda,
+          game: gamePda,
+          playerSeat: seatPda,
+          player,
+        })
+        .signers(signers)
+        .rpc();
+
+    await sendAndConfirm(
+      () => declare(adminSeatPda, admin.publicKey, { show: {} }, []),
+      "declareShowdownAction(admin, show)"
+    );
+    await sendAndConfirm(
+      () => declare(player2SeatPda, player2.publicKey, { muck: {} }, [player2]),
+      "declareShowdownAction(player2, muck)"
+    );
+
+    const allowShown = async (seatPda: anchor.web3.PublicKey) => {
+      const seat = await program.account.playerSeat.fetch(seatPda);
+      const pairIndex = game.shuffledIndices.findIndex(
+        (s: number) => s === seat.seatIndex
+      );
+      const allowances = [pairIndex * 2, pairIndex * 2 + 1].map((slot) => ({
+        pubkey: anchor.web3.PublicKey.findProgramAddressSync(
+          [handleToBytesLE(extractHandle(game.dealCards[slot])), spectator.publicKey.toBuffer()],
+          INCO_LIGHTNING_ID
+        )[0],
+        isWritable: true,
+        isSigner: false,
+      }));
+      return program.methods
+        .allowShownHand()
+        .accounts({
+          table: tablePda,
+          game: gamePda,
+          playerSeat: seatPda,
+          spectator: spectator.publicKey,
+          backend: admin.publicKey,
+          incoLightningProgram: INCO_LIGHTNING_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(allowances)
+        .rpc();
+    };
+
+    await sendAndConfirm(() => allowShown(adminSeatPda), "allowShownHand(admin)");
+
+    try {
+      await allowShown(player2SeatPda);
+      throw new Error("mucked hand was opened to a spectator");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "HandNotShown") {
+        throw err;
+      }
+      console.log("Mucked hand withheld with HandNotShown");
+    }
+  });
+
+  // ========================================
+  // TEST 5c: Rank hands on-chain
+  // ========================================
+  it("5c. Rank hands from attested card values", async () => {
+    const game = await program.account.pokerGame.fetch(gamePda);
+    const adminSeat = await program.account.playerSeat.fetch(adminSeatPda);
+    const player2Seat = await program.account.playerSeat.fetch(player2SeatPda);
+    const slotHandle = (slot: number) =>
+      extractHandle(slot < 10 ? game.dealCards[slot] : game.communityCards[slot - 10]);
+    const holeSlots = (seatIndex: number) => {
+      const pos = game.shuffledIndices.indexOf(seatIndex);
+      return [pos * 2, pos * 2 + 1];
+    };
+
+    // Decrypt with attestation and have the program check it against the stored handles
+    const attest = async (
+      slots: number[],
+      address: anchor.web3.PublicKey,
+      signMessage: (msg: Uint8Array) => Promise<Uint8Array>,
+      signer: anchor.web3.Keypair | null,
+      label: string
+    ) => {
+      const result = await decryptWithRetry(
+        slots.map((slot) => handleToDecimalString(slotHandle(slot))),
+        address,
+        signMessage,
+        label
+      );
+      const ix = await program.methods
+        .attestCards(
+          Buffer.from(slots),
+          result.plaintexts.map((p: string) => handleToBytesLE(p))
+        )
+        .accounts({
+          table: tablePda,
+          game: gamePda,
+          attester: address,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          incoLightningProgram: INCO_LIGHTNING_ID,
+        })
+        .instruction();
+      const tx = new anchor.web3.Transaction();
+      result.ed25519Instructions.forEach((edIx: anchor.web3.TransactionInstruction) =>
+        tx.add(edIx)
+      );
+      tx.add(ix);
+      const sig = await provider.sendAndConfirm(tx, signer ? [signer] : []);
+      console.log(`  attestCards(${label}) \n tx: ${sig} \n`);
+      return result.plaintexts.map(toCardIndex);
+    };
+
+    // Ranking before the board is attested is refused
+    try {
+      await program.methods
+        .rankHands()
+        .accounts({ table: tablePda, game: gamePda, backend: admin.publicKey })
+        .remainingAccounts([
+          { pubkey: adminSeatPda, isWritable: true, isSigner: false },
+        ])
+        .rpc();
+      throw new Error("rankHands ranked unattested cards");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "CardsNotAttested") {
+        throw err;
+      }
+      console.log("Unattested board rejected with CardsNotAttested");
+    }
+
+    const adminSign = getSignMessage();
+    if (!adminSign) {
+      throw new Error("admin signMessage not available");
+    }
+    // The backend attests the board, each player their own hole cards
+    const board = [
+      ...(await attest([10, 11, 12], admin.publicKey, adminSign, null, "flop")),
+      ...(await attest([13, 14], admin.publicKey, adminSign, null, "turn and river")),
+    ];
+    const adminHole = await attest(
+      holeSlots(adminSeat.seatIndex),
+      admin.publicKey,
+      adminSign,
+      null,
+      "admin hole cards"
+    );
+    const player2Hole = await attest(
+      holeSlots(player2Seat.seatIndex),
+      player2.publicKey,
+      getKeypairSignMessage(player2),
+      player2,
+      "player2 hole cards"
+    );
+
+    const attested = await program.account.pokerGame.fetch(gamePda);
+    if (attested.cardValues.slice(10).join() !== board.join()) {
+      throw new Error(`attested board ${attested.cardValues.slice(10)}, decrypted ${board}`);
+    }
+
+    // Both seats declared at showdown in 5b, so the backend can't pick a winner unranked
+    try {
+      await program.methods
+        .settleGame(Buffer.from([adminSeat.seatIndex]), game.pot)
+        .accounts({
+          table: tablePda,
+          game: gamePda,
+          vault: vaultPda,
+          rakeVault: rakeVaultPda,
+          rakeReferrerAccount: referrer.publicKey,
+          backend: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: adminSeatPda, isWritable: false, isSigner: false },
+          { pubkey: admin.publicKey, isWritable: true, isSigner: false },
+        ])
+        .rpc();
+      throw new Error("settled a contested showdown without ranking");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "HandsNotRanked") {
+        throw err;
+      }
+      console.log("Unranked settlement rejected with HandsNotRanked");
+    }
+
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .rankHands()
+          .accounts({
+            table: tablePda,
+            game: gamePda,
+            backend: admin.publicKey,
+          })
+          .remainingAccounts([
+            { pubkey: adminSeatPda, isWritable: true, isSigner: false },
+            { pubkey: player2SeatPda, isWritable: true, isSigner: false },
+          ])
+          .rpc(),
+      "rankHands"
+    );
+
+    // The on-chain winners must match scoring the decrypted cards directly
+    const [adminRank, player2Rank] = await Promise.all(
+      [adminHole, player2Hole].map(
+        (hole) =>
+          program.methods
+            .evaluateHand([...hole, ...board], attested.deckType)
+            .accounts({})
+            .view() as Promise<anchor.BN>
+      )
+    );
+    const best = anchor.BN.max(adminRank, player2Rank);
+    const expectedMask =
+      (adminRank.eq(best) ? 1 << adminSeat.seatIndex : 0) |
+      (player2Rank.eq(best) ? 1 << player2Seat.seatIndex : 0);
+
+    const ranked = await program.account.pokerGame.fetch(gamePda);
+    console.log("  board:", board, "admin:", adminHole, "player2:", player2Hole);
+    console.log("  best_hand_rank:", ranked.bestHandRank.toString(16));
+    console.log("  winners_mask:", ranked.winnersMask);
+    if (ranked.winnersMask !== expectedMask || !ranked.bestHandRank.eq(best)) {
+      throw new Error(`winners_mask ${ranked.winnersMask}, expected ${expectedMask}`);
+    }
+  });
+
+  // ========================================
+  // TEST 5d: Reveal on request
+  // ========================================
+  it("5d. A requested hand opens only after the owner approves", async () => {
+    const game = await program.account.pokerGame.fetch(gamePda);
+    const seat = await program.account.playerSeat.fetch(player3SeatPda);
+    const pairIndex = game.shuffledIndices.findIndex(
+      (s: number) => s === seat.seatIndex
+    );
+    const holeHandles = [pairIndex * 2, pairIndex * 2 + 1].map(
+      (slot) => game.dealCards[slot]
+    );
+    const allowancesFor = (requester: anchor.web3.PublicKey) =>
+      holeHandles.map((handle: any) => ({
+        pubkey: anchor.web3.PublicKey.findProgramAddressSync(
+          [handleToBytesLE(extractHandle(handle)), requester.toBuffer()],
+          INCO_LIGHTNING_ID
+        )[0],
+        isWritable: true,
+        isSigner: false,
+      }));
+    const approve = (
+      seatPda: anchor.web3.PublicKey,
+      owner: anchor.web3.Keypair,
+      requester: anchor.web3.PublicKey
+    ) =>
+      program.methods
+        .approveReveal()
+        .accounts({
+          table: tablePda,
+          game: gamePda,
+          playerSeat: seatPda,
+          player: owner.publicKey,
+          requester,
+          backend: admin.publicKey,
+          incoLightningProgram: INCO_LIGHTNING_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(allowancesFor(requester))
+        .signers([owner])
+        .rpc();
+
+    // Without a request there is nothing to approve
+    try {
+      await approve(player3SeatPda, player3, player5.publicKey);
+      throw new Error("approved a reveal nobody requested");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "NoRevealRequest") {
+        throw err;
+      }
+      console.log("Approval without a request rejected with NoRevealRequest");
+    }
+
+    const request = (requester: anchor.web3.Keypair) =>
+      program.methods
+        .requestReveal()
+        .accounts({
+          table: tablePda,
+          game: gamePda,
+          playerSeat: player3SeatPda,
+          requester: requester.publicKey,
+        })
+        .signers([requester])
+        .rpc();
+    const decline = (seatPda: anchor.web3.PublicKey, owner: anchor.web3.Keypair) =>
+      program.methods
+        .declineReveal()
+        .accounts({
+          table: tablePda,
+          game: gamePda,
+          playerSeat: seatPda,
+          player: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+
+    // player4 gets in first with a request player3 doesn't want
+    await sendAndConfirm(() => request(player4), "requestReveal(player3 by player4)");
+
+    // A pending request can't be swapped for someone else's
+    try {
+      await request(player5);
+      throw new Error("a second request replaced the pending one");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "RevealAlreadyRequested") {
+        throw err;
+      }
+      console.log("Competing request rejected with RevealAlreadyRequested");
+    }
+    const pending = await program.account.playerSeat.fetch(player3SeatPda);
+    if (!pending.revealRequestedBy?.equals(player4.publicKey)) {
+      throw new Error("pending reveal request was overwritten");
+    }
+
+    // Only the owner can turn a request down
+    let declinedByOther = false;
+    try {
+      await decline(player3SeatPda, player4);
+      declinedByOther = true;
+    } catch (err: any) {
+      console.log("Decline by another player rejected:", err?.error?.errorCode?.code ?? err?.message);
+    }
+    if (declinedByOther) {
+      throw new Error("a non-owner declined the reveal request");
+    }
+
+    await sendAndConfirm(() => decline(player3SeatPda, player3), "declineReveal(player3)");
+    const declined = await program.account.playerSeat.fetch(player3SeatPda);
+    if (declined.revealRequestedBy !== null) {
+      throw new Error("reveal request not cleared after declining");
+    }
+
+    // The seat is free again for the requester the owner wants
+    await sendAndConfirm(() => request(player5), "requestReveal(player3 by player5)");
+
+    // Only the seat's owner can approve
+    let approvedByOther = false;
+    try {
+      await approve(player3SeatPda, player4, player5.publicKey);
+      approvedByOther = true;
+    } catch (err: any) {
+      console.log("Approval by another player rejected:", err?.error?.errorCode?.code ?? err?.message);
+    }
+    if (approvedByOther) {
+      throw new Error("a non-owner approved the reveal");
+    }
+
+    await sendAndConfirm(
+      () => approve(player3SeatPda, player3, player5.publicKey),
+      "approveReveal(player3)"
+    );
+    const cleared = await program.account.playerSeat.fetch(player3SeatPda);
+    if (cleared.revealRequestedBy !== null) {
+      throw new Error("reveal request not cleared after approval");
+    }
+
+    const revealed = await decryptWithRetry(
+      holeHandles.map((h: any) => handleToDecimalString(h)),
+      player5.publicKey,
+      getKeypairSignMessage(player5),
+      "requested reveal"
+    );
+    console.log("player5 sees player3's hand:", revealed.plaintexts.map(toCardIndex));
+  });
+
+  it("5e. Short-deck rankings reorder flush and full house", async () => {
+    const STANDARD = 0;
+    const SHORT_DECK = 1;
+    const score = (cards: number[], deckType: number) =>
+      program.methods.evaluateHand(cards, deckType).accounts({}).view() as Promise<anchor.BN>;
+    const category = (rank: anchor.BN) => rank.shrn(20).toNumber();
+
+    // Card index = suit * 13 + rank (rank 0 = deuce, 4 = six)
+    // Spade flush A-Q-T-8-6 with 7h Jd
+    const flushCards = [4, 6, 8, 10, 12, 18, 35];
+    // Kings full of queens with 7c 8d
+    const fullHouseCards = [11, 24, 37, 10, 23, 44, 32];
+    // A-6-7-8-9 off-suit with Kh Jd
+    const shortWheelCards = [12, 17, 31, 45, 7, 24, 35];
+
+    const [flushStd, fullHouseStd, flushShort, fullHouseShort] = await Promise.all([
+      score(flushCards, STANDARD),
+      score(fullHouseCards, STANDARD),
+      score(flushCards, SHORT_DECK),
+      score(fullHouseCards, SHORT_DECK),
+    ]);
+    if (!fullHouseStd.gt(flushStd)) {
+      throw new Error("standard deck: full house must beat a flush");
+    }
+    if (!flushShort.gt(fullHouseShort)) {
+      throw new Error("short deck: flush must beat a full house");
+    }
+    console.log("Flush vs full house flips between standard and short deck");
+
+    const [wheelStd, wheelShort] = await Promise.all([
+      score(shortWheelCards, STANDARD),
+      score(shortWheelCards, SHORT_DECK),
+    ]);
+    if (category(wheelStd) !== 0 || category(wheelShort) !== 4) {
+      throw new Error(
+        `A-6-7-8-9 scored category ${category(wheelStd)}/${category(wheelShort)}, expected high card/straight`
+      );
+    }
+    console.log("A-6-7-8-9 is a straight only in the short deck");
+
+    // Deuces through fives are not in a short deck
+    let acceptedDeuce = false;
+    try {
+      await score([0, 4, 6, 8, 10, 18, 35], SHORT_DECK);
+      acceptedDeuce = true;
+    } catch (err: any) {
+      console.log("Short deck rejected a deuce:", err?.error?.errorCode?.code ?? err?.message);
+    }
+    if (acceptedDeuce) {
+      throw new Error("short deck scored a hand holding a deuce");
+    }
+  });
+
   // ========================================
   // TEST 6: Settle game (pay winner)
   // ========================================
   it("6. Settle game and pay winner", async () => {
-    // In a real game, backend would determine winner based on hand evaluation
-    // For this test, we'll just pick admin (seat 0) as the winner
-    const winnerSeatIndex = 0;
+    // Pay exactly the seats test 5c ranked as holding the best hand
+    const game = await program.account.pokerGame.fetch(gamePda);
+    const ranked = [
+      { seatPda: adminSeatPda, wallet: null },
+      { seatPda: player2SeatPda, wallet: player2 },
+    ];
+    settledWinners = [];
+    for (const { seatPda, wallet } of ranked) {
+      const seat = await program.account.playerSeat.fetch(seatPda);
+      if (game.winnersMask & (1 << seat.seatIndex)) {
+        settledWinners.push({ seatIndex: seat.seatIndex, seatPda, wallet });
+      }
+    }
+    const winnerSeatIndices = Buffer.from(settledWinners.map((w) => w.seatIndex));
+    const winnerAccounts = settledWinners.flatMap((w) => [
+      { pubkey: w.seatPda, isWritable: false, isSigner: false },
+      {
+        pubkey: w.wallet ? w.wallet.publicKey : admin.publicKey,
+        isWritable: true,
+        isSigner: false,
+      },
+    ]);
 
     // Final pot would include all bets collected during gameplay
     // For this test, use the initial pot (blinds only)
-    const game = await program.account.pokerGame.fetch(gamePda);
     const finalPot = game.pot;
 
     console.log("\nSettling game:");
-    console.log("  Winner: seat", winnerSeatIndex, "(admin)");
+    console.log("  Winners: seats", [...winnerSeatIndices]);
     console.log("  Final pot:", finalPot.toString());
 
+    // Settlement is a payout, so it still works on a paused table
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .pauseTable()
+          .accounts({ table: tablePda, creator: admin.publicKey })
+          .rpc(),
+      "pauseTable (main table)"
+    );
+
+    // A pot larger than the vault holds is refused before anything moves
+    const vaultLamports = await provider.connection.getBalance(vaultPda);
+    const rakeVaultBefore = await provider.connection.getBalance(rakeVaultPda);
+    const referrerBefore = await provider.connection.getBalance(referrer.publicKey);
+    try {
+      await program.methods
+        .settleGame(winnerSeatIndices, new anchor.BN(vaultLamports + 1))
+        .accounts({
+          table: tablePda,
+          game: gamePda,
+          vault: vaultPda,
+          rakeVault: rakeVaultPda,
+          rakeReferrerAccount: referrer.publicKey,
+          backend: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(winnerAccounts)
+        .rpc();
+      throw new Error("settleGame paid out more than the vault holds");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "VaultUnderfunded") {
+        throw err;
+      }
+      console.log("Inflated pot rejected with VaultUnderfunded");
+    }
+
     await sendAndConfirm(
       () =>
         program.methods
-          .settleGame(winnerSeatIndex, finalPot)
+          .settleGame(winnerSeatIndices, finalPot)
           .accounts({
             table: tablePda,
             game: gamePda,
-            winnerSeat: adminSeatPda,
-            winnerWallet: admin.publicKey,
             vault: vaultPda,
+            rakeVault: rakeVaultPda,
+            rakeReferrerAccount: referrer.publicKey,
             backend: admin.publicKey,
             systemProgram: anchor.web3.SystemProgram.programId,
           })
+          .remainingAccounts(winnerAccounts)
           .rpc(),
       "settleGame"
     );
 
+    // The vault PDA signed the payout: it paid out exactly the pot, rake included
+    const vaultAfter = await provider.connection.getBalance(vaultPda);
+    if (vaultLamports - vaultAfter !== finalPot.toNumber()) {
+      throw new Error(
+        `vault paid ${vaultLamports - vaultAfter} lamports, expected ${finalPot.toString()}`
+      );
+    }
+
+    // The flop was seen, so the pot was raked and the rake split 70/30
+    const expectedRake = Math.floor((finalPot.toNumber() * rakeBps) / 10_000);
+    const expectedReferrerCut = Math.floor((expectedRake * referrerShareBps) / 10_000);
+    const rakeVaultGain = (await provider.connection.getBalance(rakeVaultPda)) - rakeVaultBefore;
+    const referrerGain = (await provider.connection.getBalance(referrer.publicKey)) - referrerBefore;
+    if (
+      rakeVaultGain !== expectedRake - expectedReferrerCut ||
+      referrerGain !== expectedReferrerCut
+    ) {
+      throw new Error(
+        `rake split ${rakeVaultGain}/${referrerGain}, expected ${expectedRake - expectedReferrerCut}/${expectedReferrerCut}`
+      );
+    }
+    console.log(`Rake ${expectedRake}: ${rakeVaultGain} to the rake vault, ${referrerGain} to the referrer`);
+
+    // A second settlement must not pay the pot out again
+    let settledTwice = false;
+    try {
+      await program.methods
+        .settleGame(winnerSeatIndices, finalPot)
+        .accounts({
+          table: tablePda,
+          game: gamePda,
+          vault: vaultPda,
+          rakeVault: rakeVaultPda,
+          rakeReferrerAccount: referrer.publicKey,
+          backend: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(winnerAccounts)
+        .rpc();
+      settledTwice = true;
+    } catch (err: any) {
+      console.log("Second settleGame rejected:", err?.error?.errorCode?.code ?? err?.message);
+    }
+    if (settledTwice) {
+      throw new Error("settleGame succeeded twice for the same game");
+    }
+
+    await sendAndConfirm(
+      () =>
+        program.methods
+          .unpauseTable()
+          .accounts({ table: tablePda, creator: admin.publicKey })
+          .rpc(),
+      "unpauseTable (main table)"
+    );
+
     // Game PDA is closed after settlement, so we can't fetch it anymore
     console.log("\nGame settled:");
     console.log("  Game PDA closed (rent reclaimed to backend)");
-    console.log("  Winner: seat", winnerSeatIndex);
-    console.log("  Payout:", finalPot.toString(), "lamports");
+    console.log("  Winners: seats", [...winnerSeatIndices]);
+    console.log("  Payout:", finalPot.subn(expectedRake).toString(), "lamports (pot less rake)");
+  });
+
+  // ========================================
+  // TEST 6a: Withdraw chips without leaving
+  // ========================================
+  it("6a. Winner withdraws chips above the table minimum and stays seated", async () => {
+    // Withdraw from a seat test 6 actually paid; the admin's wallet backs a null entry
+    const winner = settledWinners[0];
+    if (!winner) {
+      throw new Error("test 6 settled no winners");
+    }
+    const owner = winner.wallet;
+    const ownerKey = owner ? owner.publicKey : admin.publicKey;
+    const withdraw = (amount: anchor.BN, backend: anchor.web3.Keypair | null = null) =>
+      program.methods
+        .withdrawWinnings(amount)
+        .accounts({
+          table: tablePda,
+          vault: vaultPda,
+          playerSeat: winner.seatPda,
+          player: ownerKey,
+          backend: backend ? backend.publicKey : admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([...new Set([owner, backend])].filter((k): k is anchor.web3.Keypair => k !== null))
+        .rpc();
+
+    const seatBefore = await program.account.playerSeat.fetch(winner.seatPda);
+    const excess = seatBefore.chips.sub(buyInMin);
+    if (excess.lten(0)) {
+      throw new Error(`winner at seat ${winner.seatIndex} has no chips above buy_in_min to withdraw`);
+    }
+
+    // The backend must vouch for the stack; a player can't co-sign for themselves
+    if (owner) {
+      try {
+        await withdraw(excess, owner);
+        throw new Error("withdrawal went through without the backend");
+      } catch (err: any) {
+        if (err?.error?.errorCode?.code !== "NotBackend") {
+          throw err;
+        }
+        console.log("Withdrawal without the backend rejected with NotBackend");
+      }
+    }
+
+    // The stack may not drop below buy_in_min
+    try {
+      await withdraw(excess.addn(1));
+      throw new Error("withdrawal left the stack below buy_in_min");
+    } catch (err: any) {
+      if (err?.error?.errorCode?.code !== "InsufficientChips") {
+        throw err;
+      }
+      console.log("Over-withdrawal rejected with InsufficientChips");
+    }
+
+    const vaultBefore = await provider.connection.getBalance(vaultPda);
+    await sendAndConfirm(() => withdraw(excess), "withdrawWinnings");
+
+    const seatAfter = await program.account.playerSeat.fetch(winner.seatPda);
+    const vaultGain = vaultBefore - (await provider.connection.getBalance(vaultPda));
+    if (!seatAfter.chips.eq(buyInMin) || seatAfter.seatIndex !== seatBefore.seatIndex) {
+      throw new Error(`stack ${seatAfter.chips.toString()} after withdrawal, expected buy_in_min`);
+    }
+    if (vaultGain !== excess.toNumber()) {
+      throw new Error(`vault paid ${vaultGain}, expected ${excess.toString()}`);
+    }
+    console.log(`Winner at seat ${winner.seatIndex} withdrew ${excess.toString()} and kept the seat`);
   });
 
   // ========================================
@@ -150,7 +150,13 @@ describe("generate_random: Inco e_rand Test", () => {
                 buyInMin,
                 buyInMax,
                 smallBlind,
-                backend.publicKey
+                new anchor.BN(0),
+                backend.publicKey,
+                null,
+                0,
+                new anchor.BN(0),
+                null,
+                0
             )
             .accounts({
                 table: tablePda,
@@ -395,4 +401,49 @@ describe("generate_random: Inco e_rand Test", () => {
             console.log("  Decrypt failed:", err?.message ?? String(err));
         }
     });
+
+    it("6. Rotated-out backend can no longer allow", async () => {
+        const newBackend = anchor.web3.Keypair.generate();
+
+        const sig = await program.methods
+            .setBackend(newBackend.publicKey)
+            .accounts({
+                table: tablePda,
+                creator: backend.publicKey,
+            })
+            .rpc();
+        await connection.confirmTransaction(sig, "confirmed");
+
+        const table = await program.account.pokerTable.fetch(tablePda);
+        if (!table.backend.equals(newBackend.publicKey)) {
+            throw new Error("setBackend did not update table.backend");
+        }
+        console.log("✓ Backend rotated to", newBackend.publicKey.toBase58());
+
+        const randomState = await program.account.randomState.fetch(randomStatePda);
+        const [allowancePda] = anchor.web3.PublicKey.findProgramAddressSync(
+            [handleToBytesLE(randomState.randomHandle), backend.publicKey.toBuffer()],
+            INCO_LIGHTNING_ID
+        );
+
+        try {
+            await program.methods
+                .allowRandom()
+                .accounts({
+                    table: tablePda,
+                    randomState: randomStatePda,
+                    allowanceAccount: allowancePda,
+                    backend: backend.publicKey,
+                    incoLightningProgram: INCO_LIGHTNING_ID,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .rpc();
+            throw new Error("old backend was still allowed to call allowRandom");
+        } catch (err: any) {
+            if (err?.error?.errorCode?.code !== "NotBackend") {
+                throw err;
+            }
+            console.log("✓ Old backend rejected with NotBackend");
+        }
+    });
 });